@@ -0,0 +1,84 @@
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use st_fifo_tool::*;
+
+/// Previous `FifoData::sort`, kept as a reference.
+fn insertion_sort(fifo_out_slot: &mut [OutSlot], out_slot_size: u16) {
+    for i in 1..out_slot_size as usize {
+        let temp = fifo_out_slot[i];
+        let mut j: i32 = i as i32 - 1;
+
+        while j >= 0 && fifo_out_slot[j as usize].timestamp > temp.timestamp {
+            fifo_out_slot[(j + 1) as usize] = fifo_out_slot[j as usize];
+            j -= 1;
+        }
+
+        fifo_out_slot[(j + 1) as usize] = temp;
+    }
+}
+
+/// Decoded output of 1536 accelerometer and gyroscope samples, 3x compressed.
+fn decoded_fifo(conf: &Config) -> Vec<OutSlot> {
+    let samples: Vec<_> = (0..768u32)
+        .flat_map(|i| {
+            [SensorType::Accelerometer, SensorType::Gyroscope].map(|sensor_tag| OutSlot {
+                timestamp: i * 96,
+                sensor_tag,
+                sensor_data: SensorData { data: [(i % 8) as i16, 0, 0] },
+            })
+        })
+        .collect();
+
+    let mut encoder = FifoEncoder::new(conf, true, TsDecimation::Off).unwrap();
+    let mut raw = vec![RawSlot::default(); samples.len()];
+    let n = encoder.encode(&samples, &mut raw).unwrap();
+
+    let mut fifo = FifoData::init(conf).unwrap();
+    let mut out = vec![OutSlot::default(); n as usize * MAX_SLOTS_PER_WORD];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, n), Status::Ok);
+    assert_eq!(out_size as usize, samples.len());
+    out.truncate(out_size as usize);
+    out
+}
+
+fn bench_sort(c: &mut Criterion) {
+    let conf = Config { bdr_xl: BdrGen2::Hz480.into(), bdr_gy: BdrGen2::Hz480.into(), ..Config::new(DeviceType::Lsm6dsv16x) };
+    let fifo = FifoData::init(&conf).unwrap();
+    let decoded = decoded_fifo(&conf);
+    let mut reversed = decoded.clone();
+    reversed.reverse();
+
+    for (name, input) in [("decoded", &decoded), ("reversed", &reversed)] {
+        let mut group = c.benchmark_group(format!("sort_{name}_{}", input.len()));
+
+        group.bench_function("insertion_sort", |b| {
+            b.iter_batched_ref(
+                || input.clone(),
+                |out| insertion_sort(black_box(out), input.len() as u16),
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function("sort", |b| {
+            b.iter_batched_ref(
+                || input.clone(),
+                |out| fifo.sort(black_box(out), input.len() as u16),
+                BatchSize::SmallInput,
+            )
+        });
+        group.finish();
+    }
+
+    let xl: Vec<_> = decoded.iter().copied().filter(|s| s.sensor_tag == SensorType::Accelerometer).collect();
+    let gy: Vec<_> = decoded.iter().copied().filter(|s| s.sensor_tag == SensorType::Gyroscope).collect();
+    let mut out = vec![OutSlot::default(); decoded.len()];
+
+    c.bench_function("merge_2_runs", |b| {
+        b.iter(|| {
+            let mut runs = [xl.as_slice(), gy.as_slice()];
+            fifo.merge(black_box(&mut runs), &mut out)
+        })
+    });
+}
+
+criterion_group!(benches, bench_sort);
+criterion_main!(benches);
@@ -0,0 +1,71 @@
+#![no_main]
+
+//! Feeds arbitrary configurations and FIFO streams to the tag-based decoder.
+//!
+//! Input: device, 7 BDR codes (XL, GY, VSENS, HG, dual-channel, EIS, ExtSens0),
+//! output buffer size, then reads of `len, flags, len * 7 bytes`.
+
+use libfuzzer_sys::fuzz_target;
+use st_fifo_tool::*;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((header, mut data)) = data.split_first_chunk::<9>() else {
+        return;
+    };
+    let Ok(device) = DeviceType::try_from(header[0] % 15) else {
+        return;
+    };
+    let bdr = |code: u8| Bdr::from_code(device, code & 0x0F).unwrap_or(Bdr::Off);
+
+    let conf = Config {
+        bdr_xl: bdr(header[1]),
+        bdr_gy: bdr(header[2]),
+        bdr_vsens: bdr(header[3]),
+        bdr_hg: bdr(header[4]),
+        bdr_dualc: bdr(header[5]),
+        bdr_eis: bdr(header[6]),
+        ext_sensors: [Some(ExtSensorSlot::new(ExtSensorFormat::LPS22DF, bdr(header[7]))), None, None, None],
+        ts_decimation: TsDecimation::X8,
+        ..Config::new(device)
+    };
+    let Ok(mut fifo) = FifoData::init(&conf) else {
+        return;
+    };
+
+    let mut out = vec![OutSlot::default(); header[8] as usize * 4];
+    let mut demux = Demux::<16>::new(&[SensorType::Accelerometer, SensorType::Gyroscope], OverflowPolicy::DropNewest);
+
+    while let Some(([len, flags], rest)) = data.split_first_chunk::<2>() {
+        let words: Vec<_> = rest
+            .chunks_exact(7)
+            .take(*len as usize)
+            .map(|chunk| RawSlot { fifo_data_out: chunk.try_into().unwrap() })
+            .collect();
+        data = &rest[words.len() * 7..];
+
+        let fifo_ovr = flags & 1 != 0;
+        let mut out_size = 0;
+
+        match flags >> 1 & 0x03 {
+            0 => {
+                if fifo.decode_with_overrun(&mut out, &words, &mut out_size, words.len() as u16, fifo_ovr) == Status::Ok {
+                    assert!(out_size as usize <= out.len());
+                    fifo.sort(&mut out, out_size);
+                }
+            }
+            1 => {
+                fifo.decode_reconstructed(&mut out, &words, &mut out_size, words.len() as u16, fifo_ovr, Some(u64::from(*len) << 20));
+                fifo.ts_error_bound();
+                fifo.host_time_ns(0);
+            }
+            _ => {
+                fifo.decode_into(&mut demux, &words, words.len() as u16, fifo_ovr);
+                demux.drain(SensorType::Gyroscope).count();
+            }
+        }
+
+        if words.is_empty() {
+            break;
+        }
+    }
+});
@@ -0,0 +1,65 @@
+#![no_main]
+
+//! Feeds arbitrary configurations and FIFO_DATA_OUT words to the pattern-based
+//! decoder.
+//!
+//! Input: FIFO ODR code, 4 decimation codes, DS4 content, output buffer size,
+//! then reads of `len, FIFO_PATTERN (2 bytes), len * 2 bytes`.
+
+use libfuzzer_sys::fuzz_target;
+use st_fifo_tool::*;
+
+const DECIMATIONS: [FifoDecimation; 8] = [
+    FifoDecimation::Off,
+    FifoDecimation::X1,
+    FifoDecimation::X2,
+    FifoDecimation::X3,
+    FifoDecimation::X4,
+    FifoDecimation::X8,
+    FifoDecimation::X16,
+    FifoDecimation::X32,
+];
+
+fuzz_target!(|data: &[u8]| {
+    let Some((header, mut data)) = data.split_first_chunk::<7>() else {
+        return;
+    };
+    let decimation = |code: u8| DECIMATIONS[code as usize % DECIMATIONS.len()];
+
+    let conf = Config {
+        pattern: PatternConfig {
+            odr_fifo: Bdr::from_code(DeviceType::Lsm6dsl, header[0] & 0x0F).unwrap_or(Bdr::Off),
+            dec_gy: decimation(header[1]),
+            dec_xl: decimation(header[2]),
+            dec_ds3: decimation(header[3]),
+            dec_ds4: decimation(header[4]),
+            ds4: if header[5] & 1 == 0 { Ds4Content::StepCounter } else { Ds4Content::Temperature },
+        },
+        ..Config::new(DeviceType::Lsm6dsl)
+    };
+    let Ok(mut fifo) = PatternFifo::init(&conf) else {
+        return;
+    };
+
+    let mut out = vec![OutSlot::default(); header[6] as usize];
+
+    while let Some(([len, p0, p1], rest)) = data.split_first_chunk::<3>() {
+        let words: Vec<_> = rest
+            .chunks_exact(2)
+            .take(*len as usize)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect();
+        data = &rest[words.len() * 2..];
+
+        let fifo_pattern = u16::from_le_bytes([*p0, *p1]) % (fifo.pattern_words() + 1);
+        let mut out_size = 0;
+
+        if fifo.decode(&mut out, &words, &mut out_size, words.len() as u16, fifo_pattern) == Status::Ok {
+            assert!(out_size as usize <= out.len());
+        }
+
+        if words.is_empty() {
+            break;
+        }
+    }
+});
@@ -0,0 +1,174 @@
+#ifndef ST_FIFO_H
+#define ST_FIFO_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#include <stdint.h>
+
+typedef enum {
+    ST_FIFO_OK  = 0,
+    ST_FIFO_ERR = 1,
+} st_fifo_status;
+
+typedef enum {
+    ST_FIFO_LSM6DSR     = 0,
+    ST_FIFO_LSM6DSRX    = 1,
+    ST_FIFO_ASM330LHH   = 2,
+    ST_FIFO_ASM330LHHX  = 3,
+    ST_FIFO_ISM330DHCX  = 4,
+    ST_FIFO_LSM6DSO     = 5,
+    ST_FIFO_LSM6DSOX    = 6,
+    ST_FIFO_LSM6DSO32   = 7,
+    ST_FIFO_LSM6DSO32X  = 8,
+    ST_FIFO_LSM6DSV     = 9,
+    ST_FIFO_LSM6DSV16X  = 10,
+    ST_FIFO_LSM6DSV32X  = 11,
+    ST_FIFO_LSM6DSV80X  = 12,
+    ST_FIFO_LSM6DSV320X = 13,
+    ST_FIFO_ISM6HG256X  = 14,
+} st_fifo_device;
+
+typedef enum {
+    ST_FIFO_GYROSCOPE             = 0,
+    ST_FIFO_ACCELEROMETER         = 1,
+    ST_FIFO_TEMPERATURE           = 2,
+    ST_FIFO_EXT_SENSOR0           = 3,
+    ST_FIFO_EXT_SENSOR1           = 4,
+    ST_FIFO_EXT_SENSOR2           = 5,
+    ST_FIFO_EXT_SENSOR3           = 6,
+    ST_FIFO_STEP_COUNTER          = 7,
+    ST_FIFO_6X_GAME_RV            = 8,
+    ST_FIFO_6X_GEOM_RV            = 9,
+    ST_FIFO_9X_RV                 = 10,
+    ST_FIFO_GYRO_BIAS             = 11,
+    ST_FIFO_GRAVITY               = 12,
+    ST_FIFO_MAGNETOMETER_CALIB    = 13,
+    ST_FIFO_EXT_SENSOR_NACK       = 14,
+    ST_FIFO_MLC_RESULT            = 15,
+    ST_FIFO_MLC_FILTER            = 16,
+    ST_FIFO_MLC_FEATURE           = 17,
+    ST_FIFO_DUALC_ACCELEROMETER   = 18,
+    ST_FIFO_EIS_GYROSCOPE         = 19,
+    ST_FIFO_NONE                  = 20,
+    ST_FIFO_HIGH_G_ACCELEROMETER  = 21,
+    ST_FIFO_DATA_LOSS             = 22, /* reported when samples are lost, see st_fifo_set_report_events */
+    ST_FIFO_BDR_CHANGE            = 23, /* reported on an ODRCHG word, see st_fifo_set_report_events */
+    ST_FIFO_CUSTOM_0              = 24, /* tags decoded by the application: not output */
+    ST_FIFO_CUSTOM_1              = 25,
+    ST_FIFO_CUSTOM_2              = 26,
+    ST_FIFO_CUSTOM_3              = 27,
+} st_fifo_sensor_type;
+
+typedef struct {
+    st_fifo_device device;
+    float bdr_xl;    /* accelerometer batch data rate in Hz */
+    float bdr_gy;    /* gyroscope batch data rate in Hz */
+    float bdr_vsens; /* virtual sensor batch data rate in Hz */
+} st_fifo_conf;
+
+typedef struct {
+    uint8_t fifo_data_out[7]; /* registers from mems (78h -> 7Dh) */
+} st_fifo_raw_slot;
+
+typedef struct {
+    uint32_t timestamp;
+    st_fifo_sensor_type sensor_tag;
+    union {
+        uint8_t raw_data[6];
+        int16_t data[3];
+        struct {
+            int16_t x;
+            int16_t y;
+            int16_t z;
+        };
+        struct {
+            int16_t temp;
+        };
+        struct {
+            uint16_t steps;
+            uint8_t steps_t[4];
+        };
+        struct {
+            uint16_t qx;
+            uint16_t qy;
+            uint16_t qz;
+        };
+        struct {
+            uint8_t nack;
+        };
+        struct {
+            uint8_t mlc_res;
+            uint8_t mlc_idx;
+            uint8_t mlc_t[4];
+        };
+        struct {
+            uint16_t mlc_value;
+            uint16_t mlc_id;
+            uint16_t reserved;
+        };
+    } sensor_data;
+} st_fifo_out_slot;
+
+/**
+ * Initialize the decoder.
+ *
+ * The batch data rates must be values supported by the device (0 if the
+ * sensor is not batched): other rates are an error.
+ *
+ * Returns:
+ *   ST_FIFO_OK on success, ST_FIFO_ERR on invalid configuration.
+ */
+st_fifo_status st_fifo_init(st_fifo_conf *conf);
+
+/**
+ * Not in the C library: output the ST_FIFO_DATA_LOSS and ST_FIFO_BDR_CHANGE
+ * slots too when report_events is not 0. st_fifo_init turns them off.
+ *
+ * Returns:
+ *   ST_FIFO_OK on success, ST_FIFO_ERR if st_fifo_init was not called.
+ */
+st_fifo_status st_fifo_set_report_events(uint8_t report_events);
+
+/**
+ * Decode stream_size FIFO words.
+ *
+ * fifo_out_slot must have room for up to 3 samples per word, or 4 slots
+ * per word after st_fifo_set_report_events (a data loss can follow the
+ * samples of a word); the number of decoded slots is stored in out_slot_size.
+ *
+ * Returns:
+ *   ST_FIFO_OK on success, ST_FIFO_ERR on invalid tag or if st_fifo_init
+ *   was not called.
+ */
+st_fifo_status st_fifo_decode(st_fifo_out_slot *fifo_out_slot,
+                              st_fifo_raw_slot *fifo_raw_slot,
+                              uint16_t *out_slot_size,
+                              uint16_t stream_size);
+
+/**
+ * Sort the decoded samples by timestamp (stable).
+ */
+void st_fifo_sort(st_fifo_out_slot *fifo_out_slot, uint16_t out_slot_size);
+
+/**
+ * Return the number of samples of sensor_type.
+ */
+uint16_t st_fifo_get_sensor_occurrence(st_fifo_out_slot *fifo_out_slot,
+                                       uint16_t out_slot_size,
+                                       st_fifo_sensor_type sensor_type);
+
+/**
+ * Copy the samples of sensor_type to sensor_out_slot.
+ */
+void st_fifo_extract_sensor(st_fifo_out_slot *sensor_out_slot,
+                            st_fifo_out_slot *fifo_out_slot,
+                            uint16_t out_slot_size,
+                            st_fifo_sensor_type sensor_type);
+
+#ifdef __cplusplus
+} /* extern "C" */
+#endif
+
+#endif /* ST_FIFO_H */
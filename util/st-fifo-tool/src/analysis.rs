@@ -0,0 +1,212 @@
+//! Statistics of decoded FIFO recordings, to qualify a board from a FIFO dump.
+//!
+//! The functions take the decoded samples of a whole recording (in any order:
+//! they are sorted by timestamp) and the `Config` used to decode them. The
+//! timestamps are converted to seconds with `Config::time_base`; the nominal
+//! rates are the configured ones, so a recording with ODR changes is best
+//! analyzed piecewise, split at the `SensorType::BdrChange` slots.
+
+use std::vec::Vec;
+
+use crate::{Bdr, Config, Measurement, OutSlot, SensorType, Vector3};
+
+/// Timing of the samples of a sensor, see `odr`.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OdrReport {
+    pub samples: usize,
+    pub nominal_hz: f64,
+    pub effective_hz: f64, // sample intervals (dropped samples included) per second
+    pub error_ppm: f64,    // effective against nominal rate
+    pub jitter_rms_us: f64, // RMS deviation of the intervals from their mean, gaps excluded
+    pub jitter_max_us: f64, // largest deviation of an interval from the mean, gaps excluded
+    pub dropped: u32,      // missing samples, from the intervals longer than 1.5 periods
+}
+
+/// Noise of the samples of a sensor in physical units, see `noise`.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseReport {
+    pub samples: usize,
+    pub mean: [f64; 3],
+    pub rms: [f64; 3], // RMS deviation from the mean (standard deviation)
+}
+
+/// Allan deviation at an averaging time, see `allan_deviation`.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AllanPoint {
+    pub tau: f64,       // averaging time in s
+    pub adev: [f64; 3], // per axis, in the unit of the samples
+}
+
+/// Returns the configured batch data rate of a sensor.
+///
+/// The virtual sensors (SFLP outputs) use `bdr_vsens`; the sensors without a
+/// rate of their own (step counter, MLC outputs, ...) are `Bdr::Off`.
+pub fn nominal_bdr(conf: &Config, sensor_type: SensorType) -> Bdr {
+    match sensor_type {
+        SensorType::Accelerometer => conf.bdr_xl,
+        SensorType::Gyroscope => conf.bdr_gy,
+        SensorType::HighGAccel => conf.bdr_hg,
+        SensorType::DualAccel => conf.bdr_dualc,
+        SensorType::EisGyro => conf.bdr_eis,
+        SensorType::Temperature => conf.bdr_temp,
+        SensorType::ExtSensor0 | SensorType::ExtSensor1 | SensorType::ExtSensor2 | SensorType::ExtSensor3 => {
+            let n = sensor_type as usize - SensorType::ExtSensor0 as usize;
+            conf.ext_sensors[n].map_or(Bdr::Off, |slot| slot.bdr)
+        }
+        SensorType::GameRv6x | SensorType::GeomRv6x | SensorType::Rv9x | SensorType::GyroBias | SensorType::Gravity => {
+            conf.bdr_vsens
+        }
+        _ => Bdr::Off,
+    }
+}
+
+/// Measures the output data rate, the timestamp jitter and the dropped samples
+/// of a sensor.
+///
+/// Returns `None` if the sensor has no nominal rate or less than 2 samples
+/// spanning some time.
+pub fn odr(samples: &[OutSlot], conf: &Config, sensor_type: SensorType) -> Option<OdrReport> {
+    let nominal_hz = nominal_bdr(conf, sensor_type).hz() as f64;
+    let times = timestamps(samples, sensor_type);
+
+    let (&first, &last) = (times.first()?, times.last()?);
+    if nominal_hz == 0.0 || last == first {
+        return None;
+    }
+
+    let tick = conf.time_base.tick_ps() as f64 * 1e-12;
+    let period = 1.0 / (nominal_hz * tick); // in ticks
+
+    let mut periods = 0;
+    let mut intervals = Vec::with_capacity(times.len());
+
+    for pair in times.windows(2) {
+        let dt = (pair[1] - pair[0]) as f64;
+        let n = libm::round(dt / period).max(1.0) as u32;
+
+        periods += n;
+        if n == 1 {
+            intervals.push(dt);
+        }
+    }
+
+    let effective_hz = periods as f64 / ((last - first) as f64 * tick);
+    let mean = intervals.iter().sum::<f64>() / intervals.len().max(1) as f64;
+    let deviations = intervals.iter().map(|dt| dt - mean);
+    let jitter_rms = libm::sqrt(deviations.clone().map(|d| d * d).sum::<f64>() / intervals.len().max(1) as f64);
+    let jitter_max = deviations.fold(0.0, |max: f64, d| max.max(d.abs()));
+
+    Some(OdrReport {
+        samples: times.len(),
+        nominal_hz,
+        effective_hz,
+        error_ppm: (effective_hz / nominal_hz - 1.0) * 1e6,
+        jitter_rms_us: jitter_rms * tick * 1e6,
+        jitter_max_us: jitter_max * tick * 1e6,
+        dropped: periods - (times.len() as u32 - 1),
+    })
+}
+
+/// Computes the per-axis mean and RMS noise of a sensor, in the units of
+/// `OutSlot::to_si` (m/s², dps, µT).
+///
+/// Returns `None` if the sensor has no 3-axis measurement or no samples.
+pub fn noise(samples: &[OutSlot], conf: &Config, sensor_type: SensorType) -> Option<NoiseReport> {
+    let values = vectors(samples, conf, sensor_type);
+    if values.is_empty() {
+        return None;
+    }
+
+    let n = values.len() as f64;
+    let mut mean = [0.0; 3];
+    let mut rms = [0.0; 3];
+
+    for value in &values {
+        for axis in 0..3 {
+            mean[axis] += value[axis] / n;
+        }
+    }
+
+    for value in &values {
+        for axis in 0..3 {
+            rms[axis] += (value[axis] - mean[axis]) * (value[axis] - mean[axis]) / n;
+        }
+    }
+
+    Some(NoiseReport { samples: values.len(), mean, rms: rms.map(libm::sqrt) })
+}
+
+/// Computes the overlapping Allan deviation of a sensor, typically the
+/// gyroscope, at octave-spaced averaging times from the nominal sample period
+/// up to a quarter of the recording.
+///
+/// The samples are taken at the nominal rate: the dropped samples are not
+/// filled in. Returns an empty vector if the sensor has no nominal rate or no
+/// 3-axis measurement.
+pub fn allan_deviation(samples: &[OutSlot], conf: &Config, sensor_type: SensorType) -> Vec<AllanPoint> {
+    let nominal_hz = nominal_bdr(conf, sensor_type).hz() as f64;
+    let values = vectors(samples, conf, sensor_type);
+
+    if nominal_hz == 0.0 {
+        return Vec::new();
+    }
+
+    let tau0 = 1.0 / nominal_hz;
+
+    // integral of the samples: the angle of a gyroscope
+    let mut theta = Vec::with_capacity(values.len() + 1);
+    theta.push([0.0; 3]);
+
+    for value in &values {
+        let last: [f64; 3] = theta[theta.len() - 1];
+        theta.push([0, 1, 2].map(|axis| last[axis] + value[axis] * tau0));
+    }
+
+    let mut points = Vec::new();
+    let mut m = 1;
+
+    while 4 * m <= values.len() {
+        let tau = m as f64 * tau0;
+        let terms = theta.len() - 2 * m;
+        let mut sum = [0.0; 3];
+
+        for k in 0..terms {
+            for (axis, sum) in sum.iter_mut().enumerate() {
+                let d = theta[k + 2 * m][axis] - 2.0 * theta[k + m][axis] + theta[k][axis];
+                *sum += d * d;
+            }
+        }
+
+        let adev = sum.map(|sum| libm::sqrt(sum / (2.0 * tau * tau * terms as f64)));
+        points.push(AllanPoint { tau, adev });
+        m *= 2;
+    }
+
+    points
+}
+
+/// Returns the sorted timestamps of a sensor.
+fn timestamps(samples: &[OutSlot], sensor_type: SensorType) -> Vec<u32> {
+    let mut times: Vec<_> = samples.iter().filter(|s| s.sensor_tag == sensor_type).map(|s| s.timestamp).collect();
+    times.sort_unstable();
+    times
+}
+
+/// Returns the 3-axis measurements of a sensor, in timestamp order.
+fn vectors(samples: &[OutSlot], conf: &Config, sensor_type: SensorType) -> Vec<[f64; 3]> {
+    let mut slots: Vec<_> = samples.iter().filter(|s| s.sensor_tag == sensor_type).collect();
+    slots.sort_by_key(|s| s.timestamp);
+
+    slots
+        .into_iter()
+        .filter_map(|slot| match slot.to_si(conf) {
+            Measurement::Acceleration(v) | Measurement::AngularRate(v) | Measurement::MagneticField(v) => Some(v),
+            Measurement::Gravity(v) | Measurement::GyroBias(v) => Some(v),
+            _ => None,
+        })
+        .map(|Vector3 { x, y, z }| [x as f64, y as f64, z as f64])
+        .collect()
+}
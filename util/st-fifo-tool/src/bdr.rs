@@ -0,0 +1,207 @@
+use crate::{DEVICES, DeviceType, Status};
+
+/// Batch data rates of the LSM6DSO-class devices (LSM6DSR, LSM6DSO, ASM330LHH, ...).
+///
+/// The discriminant is the BDR register code, as read from an ODRCHG word.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BdrGen1 {
+    Hz13 = 1,
+    Hz26 = 2,
+    Hz52 = 3,
+    Hz104 = 4,
+    Hz208 = 5,
+    Hz416 = 6,
+    Hz833 = 7,
+    Hz1666 = 8,
+    Hz3333 = 9,
+    Hz6666 = 10,
+    Hz1_625 = 11, // accelerometer and virtual sensors only
+}
+
+/// Batch data rates of the LSM6DSV-class and high-g devices.
+///
+/// The discriminant is the BDR register code, as read from an ODRCHG word.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BdrGen2 {
+    Hz1_875 = 1,
+    Hz7_5 = 2,
+    Hz15 = 3,
+    Hz30 = 4,
+    Hz60 = 5,
+    Hz120 = 6,
+    Hz240 = 7,
+    Hz480 = 8,
+    Hz960 = 9,
+    Hz1920 = 10,
+    Hz3840 = 11,
+    Hz7680 = 12,
+}
+
+impl TryFrom<u8> for BdrGen1 {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(BdrGen1::Hz13),
+            2 => Ok(BdrGen1::Hz26),
+            3 => Ok(BdrGen1::Hz52),
+            4 => Ok(BdrGen1::Hz104),
+            5 => Ok(BdrGen1::Hz208),
+            6 => Ok(BdrGen1::Hz416),
+            7 => Ok(BdrGen1::Hz833),
+            8 => Ok(BdrGen1::Hz1666),
+            9 => Ok(BdrGen1::Hz3333),
+            10 => Ok(BdrGen1::Hz6666),
+            11 => Ok(BdrGen1::Hz1_625),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TryFrom<u8> for BdrGen2 {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(BdrGen2::Hz1_875),
+            2 => Ok(BdrGen2::Hz7_5),
+            3 => Ok(BdrGen2::Hz15),
+            4 => Ok(BdrGen2::Hz30),
+            5 => Ok(BdrGen2::Hz60),
+            6 => Ok(BdrGen2::Hz120),
+            7 => Ok(BdrGen2::Hz240),
+            8 => Ok(BdrGen2::Hz480),
+            9 => Ok(BdrGen2::Hz960),
+            10 => Ok(BdrGen2::Hz1920),
+            11 => Ok(BdrGen2::Hz3840),
+            12 => Ok(BdrGen2::Hz7680),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<BdrGen1> for u8 {
+    fn from(value: BdrGen1) -> Self {
+        value as u8
+    }
+}
+
+impl From<BdrGen2> for u8 {
+    fn from(value: BdrGen2) -> Self {
+        value as u8
+    }
+}
+
+/// Batch data rate of a sensor: off, or a rate of the device generation.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Bdr {
+    #[default]
+    Off,
+    Gen1(BdrGen1),
+    Gen2(BdrGen2),
+}
+
+impl From<BdrGen1> for Bdr {
+    fn from(value: BdrGen1) -> Self {
+        Bdr::Gen1(value)
+    }
+}
+
+impl From<BdrGen2> for Bdr {
+    fn from(value: BdrGen2) -> Self {
+        Bdr::Gen2(value)
+    }
+}
+
+impl Bdr {
+    /// Converts a BDR register code of `device` (0 is off).
+    pub fn from_code(device: DeviceType, code: u8) -> Option<Self> {
+        Bdr::from_fifo_ver_code(device.fifo_ver(), code)
+    }
+
+    /// Converts a BDR register code of a FIFO generation (0 is off).
+    pub(crate) fn from_fifo_ver_code(fifo_ver: u8, code: u8) -> Option<Self> {
+        match (code, fifo_ver) {
+            (0, _) => Some(Bdr::Off),
+            (code, 0) => BdrGen1::try_from(code).ok().map(Bdr::Gen1),
+            (code, _) => BdrGen2::try_from(code).ok().map(Bdr::Gen2),
+        }
+    }
+
+    /// Returns the rate of `device` equal to `hz` (0.0 is off); rates that are
+    /// not exactly a value of the device table are an error.
+    pub fn from_hz(device: DeviceType, hz: f32) -> Result<Self, Status> {
+        let table = &DEVICES[device.fifo_ver() as usize].bdr_acc;
+
+        match table.iter().position(|&bdr| bdr == hz) {
+            Some(code) => Bdr::from_code(device, code as u8).ok_or(Status::Err),
+            None => Err(Status::Err),
+        }
+    }
+
+    /// Returns the accelerometer/gyroscope BDR register code, as in the ODRCHG
+    /// words (0 when off).
+    ///
+    /// The code is an index in `Device::bdr_acc` and `Device::dtime`, not in
+    /// every table: the high-g rates start at index 3 of `Device::bdr_hg`, while
+    /// `BdrGen2::Hz480` has code 8. Use `dtime` for the time slot of a rate.
+    pub const fn code(&self) -> u8 {
+        match self {
+            Bdr::Off => 0,
+            Bdr::Gen1(bdr) => *bdr as u8,
+            Bdr::Gen2(bdr) => *bdr as u8,
+        }
+    }
+
+    /// Returns the rate in Hz (0.0 when off).
+    pub fn hz(&self) -> f32 {
+        match self {
+            Bdr::Off => 0.0,
+            Bdr::Gen1(bdr) => DEVICES[0].bdr_acc[*bdr as usize],
+            Bdr::Gen2(bdr) => DEVICES[1].bdr_acc[*bdr as usize],
+        }
+    }
+
+    /// Returns the sample period on `device` in timestamp ticks (0 when off), or
+    /// an error if the rate belongs to another generation.
+    pub fn dtime(&self, device: DeviceType) -> Result<u32, Status> {
+        let table = &DEVICES[device.fifo_ver() as usize];
+        Ok(table.dtime[self.index(device, &table.bdr_acc)?])
+    }
+
+    pub const fn is_off(&self) -> bool {
+        matches!(self, Bdr::Off)
+    }
+
+    /// Returns true if the temperature can be batched at this rate on `device`:
+    /// 1.6, 12.5 or 52 Hz on the fifo_ver 0 parts, 1.875, 15 or 60 Hz on the
+    /// fifo_ver 1 and 2 parts (or off).
+    pub(crate) fn is_temp_rate(&self, device: DeviceType) -> bool {
+        matches!(
+            (self, device.fifo_ver()),
+            (Bdr::Off, _)
+                | (Bdr::Gen1(BdrGen1::Hz1_625 | BdrGen1::Hz13 | BdrGen1::Hz52), 0)
+                | (Bdr::Gen2(BdrGen2::Hz1_875 | BdrGen2::Hz15 | BdrGen2::Hz60), 1..)
+        )
+    }
+
+    /// Returns the index of the rate in a BDR table of `device`, or an error if
+    /// the rate belongs to another generation or is not supported by the table.
+    pub(crate) fn index(&self, device: DeviceType, table: &[f32; 16]) -> Result<usize, Status> {
+        match (self, device.fifo_ver()) {
+            (Bdr::Off, _) | (Bdr::Gen1(_), 0) | (Bdr::Gen2(_), 1..) => {}
+            _ => return Err(Status::Err),
+        }
+
+        let hz = self.hz();
+        table.iter().position(|&bdr| bdr == hz).ok_or(Status::Err)
+    }
+}
@@ -0,0 +1,611 @@
+//! Decodes a raw FIFO dump (7-byte words: tag + 6 data bytes) captured from a
+//! TAG-based IMU and prints the sorted samples as CSV or JSON Lines.
+//!
+//! A summary with the per-sensor sample count, the data losses detected by the
+//! decoder and the ODR changes is printed on stderr.
+
+use std::fmt::Write as _;
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+use std::{env, fs};
+
+use st_fifo_tool::{
+    Bdr, Config, DEVICES, DataLossCause, DeviceType, ExtSensorFormat, ExtSensorSlot, FifoData, FifoStats, GyFullScale,
+    HgFullScale, MAX_SLOTS_PER_WORD, Measurement, OutSlot, RawSlot, SensorType, Status, Tag, XlFullScale, analysis,
+};
+
+const USAGE: &str = "\
+Usage: st-fifo-decode --device <name> [options] [input]
+
+Reads the FIFO words from <input> (stdin if missing or '-').
+
+Options:
+  --device <name>          lsm6dso, lsm6dsv16x, lsm6dsv320x, ... (see --list-devices)
+  --bdr-xl <Hz>            accelerometer batch data rate
+  --bdr-gy <Hz>            gyroscope batch data rate
+  --bdr-vsens <Hz>         virtual sensor batch data rate
+  --bdr-hg <Hz>            high-g accelerometer batch data rate
+  --bdr-dualc <Hz>         dual-channel accelerometer batch data rate
+  --bdr-eis <Hz>           EIS gyroscope batch data rate
+  --bdr-temp <Hz>          temperature batch data rate
+  --fs-xl <g>              accelerometer full scale (default: lowest)
+  --fs-gy <dps>            gyroscope full scale (default: lowest)
+  --fs-hg <g>              high-g accelerometer full scale (default: lowest)
+  --fs-dualc <g>           dual-channel accelerometer full scale (default: lowest)
+  --fs-eis <dps>           EIS gyroscope full scale (default: lowest)
+  --freq-fine <value>      FREQ_FINE register content (signed)
+  --ext-sensor <n>:<format>:<Hz>
+                           sensor-hub slot 0..3 read as lis2mdl, lps22df or raw
+  --input-format <bin|hex> raw bytes or hex text (default: hex for .hex/.txt files)
+  --output <csv|jsonl>     output format (default: csv)
+  --analysis               print the ODR accuracy, jitter, drops, noise and the
+                           gyroscope Allan deviation of the recording
+  --list-devices           print the supported devices
+  -h, --help               print this help";
+
+const DEVICE_NAMES: [(&str, DeviceType); 15] = [
+    ("lsm6dsr", DeviceType::Lsm6dsr),
+    ("lsm6dsrx", DeviceType::Lsm6dsrx),
+    ("asm330lhh", DeviceType::Asm330lhh),
+    ("asm330lhhx", DeviceType::Asm330lhhx),
+    ("ism330dhcx", DeviceType::Ism330dhcx),
+    ("lsm6dso", DeviceType::Lsm6dso),
+    ("lsm6dsox", DeviceType::Lsm6dsox),
+    ("lsm6dso32", DeviceType::Lsm6dso32),
+    ("lsm6dso32x", DeviceType::Lsm6dso32x),
+    ("lsm6dsv", DeviceType::Lsm6dsv),
+    ("lsm6dsv16x", DeviceType::Lsm6dsv16x),
+    ("lsm6dsv32x", DeviceType::Lsm6dsv32x),
+    ("lsm6dsv80x", DeviceType::Lsm6dsv80x),
+    ("lsm6dsv320x", DeviceType::Lsm6dsv320x),
+    ("ism6hg256x", DeviceType::Ism6hg256x),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum InputFormat {
+    Bin,
+    Hex,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Csv,
+    Jsonl,
+}
+
+struct Args {
+    config: Config,
+    input: Option<String>,
+    input_format: Option<InputFormat>,
+    output_format: OutputFormat,
+    analysis: bool,
+}
+
+/// BDR configuration in effect from `timestamp` on.
+struct OdrChange {
+    word: usize,
+    timestamp: u32,
+    bdr_xl: f32,
+    bdr_gy: f32,
+    bdr_vsens: f32,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("error: {msg}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(msg) => {
+            eprintln!("error: {msg}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let input = read_input(args.input.as_deref())?;
+    let input_format = args.input_format.unwrap_or(match args.input.as_deref() {
+        Some(path) if path.ends_with(".hex") || path.ends_with(".txt") => InputFormat::Hex,
+        _ => InputFormat::Bin,
+    });
+    let bytes = match input_format {
+        InputFormat::Bin => input,
+        InputFormat::Hex => parse_hex(&String::from_utf8_lossy(&input))?,
+    };
+
+    if bytes.len() % 7 != 0 {
+        return Err(format!("input length ({} bytes) is not a multiple of 7", bytes.len()));
+    }
+
+    let conf = &args.config;
+    let mut fifo = FifoData::init(conf).map_err(|_| "invalid configuration".to_string())?;
+    let mut samples: Vec<OutSlot> = Vec::new();
+    let mut odr_changes = vec![OdrChange {
+        word: 0,
+        timestamp: 0,
+        bdr_xl: conf.bdr_xl.hz(),
+        bdr_gy: conf.bdr_gy.hz(),
+        bdr_vsens: conf.bdr_vsens.hz(),
+    }];
+    let mut errors = 0;
+
+    // Words are decoded one at a time: an invalid word only drops itself, and
+    // the ODR changes are tied to the samples decoded so far.
+    for (word, chunk) in bytes.chunks_exact(7).enumerate() {
+        let mut raw_slot = [RawSlot::default()];
+        let mut out_slot = [OutSlot::default(); MAX_SLOTS_PER_WORD];
+        let mut out_slot_size = 0;
+
+        raw_slot[0].fifo_data_out.copy_from_slice(chunk);
+
+        if fifo.decode_with_overrun(&mut out_slot, &raw_slot, &mut out_slot_size, 1, false) == Status::Err {
+            eprintln!("warning: word {word}: {}", word_error(chunk, conf.device));
+            errors += 1;
+            continue;
+        }
+
+        for slot in &out_slot[..out_slot_size as usize] {
+            if slot.sensor_tag == SensorType::BdrChange {
+                let bdr = slot.sensor_data.to_bdr_change();
+
+                odr_changes.push(OdrChange {
+                    word,
+                    timestamp: slot.timestamp,
+                    bdr_xl: bdr.xl.hz(),
+                    bdr_gy: bdr.gy.hz(),
+                    bdr_vsens: bdr.vsens.hz(),
+                });
+            } else {
+                samples.push(*slot);
+            }
+        }
+    }
+
+    // The decoder returns the samples in FIFO order: sort them by timestamp,
+    // keeping the FIFO order of samples with the same timestamp.
+    samples.sort_by_key(|s| s.timestamp);
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut line = String::new();
+
+    if args.output_format == OutputFormat::Csv {
+        writeln!(out, "ticks,time_ns,sensor,raw_x,raw_y,raw_z,x,y,z,w,unit").map_err(|e| e.to_string())?;
+    }
+
+    for sample in &samples {
+        match args.output_format {
+            OutputFormat::Csv => write_csv(&mut line, sample, conf),
+            OutputFormat::Jsonl => write_jsonl(&mut line, sample, conf),
+        }
+        .map_err(|e| e.to_string())?;
+
+        out.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        line.clear();
+    }
+
+    out.flush().map_err(|e| e.to_string())?;
+
+    print_summary(&samples, &odr_changes, fifo.stats(), errors);
+
+    if args.analysis {
+        // the nominal rates are the configured ones: stop at the first ODR change
+        let end = match odr_changes.get(1) {
+            Some(change) => {
+                eprintln!("analysis of the samples before the first ODR change (tick {})", change.timestamp);
+                samples.partition_point(|s| s.timestamp < change.timestamp)
+            }
+            None => samples.len(),
+        };
+        print_analysis(&samples[..end], conf);
+    }
+
+    Ok(())
+}
+
+/// Returns the reason why the decoder rejected a word.
+fn word_error(word: &[u8], device: DeviceType) -> String {
+    let fifo_ver = device.fifo_ver();
+    let tag = word[0] >> 3;
+
+    if fifo_ver == 0 && word[0].count_ones() & 1 == 0 {
+        format!("parity error in tag byte 0x{:02X}", word[0])
+    } else if tag > DEVICES[fifo_ver as usize].tag_valid_limit || Tag::from_raw(tag, fifo_ver).is_err() {
+        format!("invalid tag 0x{tag:02X} (tag byte 0x{:02X})", word[0])
+    } else {
+        "output buffer overflow".to_string()
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut device = None;
+    let mut bdr_hz = [0.0f32; 7];
+    let mut fs_xl = None;
+    let mut fs_gy = None;
+    let mut fs_hg = None;
+    let mut fs_dualc = None;
+    let mut fs_eis = None;
+    let mut freq_fine = None;
+    let mut ext_sensors = [None; 4];
+    let mut input = None;
+    let mut input_format = None;
+    let mut output_format = OutputFormat::Csv;
+    let mut analysis = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for {arg}"));
+
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(None);
+            }
+            "--list-devices" => {
+                for (name, _) in DEVICE_NAMES {
+                    println!("{name}");
+                }
+                return Ok(None);
+            }
+            "--device" => {
+                let name = value()?.to_ascii_lowercase();
+                let found = DEVICE_NAMES.iter().find(|(n, _)| *n == name);
+                device = Some(found.ok_or_else(|| format!("unknown device '{name}'"))?.1);
+            }
+            "--bdr-xl" => bdr_hz[0] = parse_number(&arg, &value()?)?,
+            "--bdr-gy" => bdr_hz[1] = parse_number(&arg, &value()?)?,
+            "--bdr-vsens" => bdr_hz[2] = parse_number(&arg, &value()?)?,
+            "--bdr-hg" => bdr_hz[3] = parse_number(&arg, &value()?)?,
+            "--bdr-dualc" => bdr_hz[4] = parse_number(&arg, &value()?)?,
+            "--bdr-eis" => bdr_hz[5] = parse_number(&arg, &value()?)?,
+            "--bdr-temp" => bdr_hz[6] = parse_number(&arg, &value()?)?,
+            "--fs-xl" => fs_xl = Some(parse_fs_xl(&value()?)?),
+            "--fs-gy" => fs_gy = Some(parse_fs_gy(&value()?)?),
+            "--fs-hg" => fs_hg = Some(parse_fs_hg(&value()?)?),
+            "--fs-dualc" => fs_dualc = Some(parse_fs_xl(&value()?)?),
+            "--fs-eis" => fs_eis = Some(parse_fs_gy(&value()?)?),
+            "--freq-fine" => freq_fine = Some(parse_number::<i8>(&arg, &value()?)?),
+            "--ext-sensor" => {
+                let (n, format, bdr) = parse_ext_sensor(&value()?)?;
+                ext_sensors[n] = Some((format, bdr));
+            }
+            "--input-format" => {
+                input_format = Some(match value()?.as_str() {
+                    "bin" => InputFormat::Bin,
+                    "hex" => InputFormat::Hex,
+                    other => return Err(format!("unknown input format '{other}'")),
+                })
+            }
+            "--output" => {
+                output_format = match value()?.as_str() {
+                    "csv" => OutputFormat::Csv,
+                    "jsonl" | "json" => OutputFormat::Jsonl,
+                    other => return Err(format!("unknown output format '{other}'")),
+                }
+            }
+            "--analysis" => analysis = true,
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{arg}'")),
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+
+    let device = device.ok_or("--device is required")?;
+    let bdr = |hz: f32| Bdr::from_hz(device, hz).map_err(|_| format!("{hz} Hz is not a batch data rate of {device:?}"));
+    let mut config = Config {
+        bdr_xl: bdr(bdr_hz[0])?,
+        bdr_gy: bdr(bdr_hz[1])?,
+        bdr_vsens: bdr(bdr_hz[2])?,
+        bdr_hg: bdr(bdr_hz[3])?,
+        bdr_dualc: bdr(bdr_hz[4])?,
+        bdr_eis: bdr(bdr_hz[5])?,
+        bdr_temp: bdr(bdr_hz[6])?,
+        ..Config::new(device)
+    };
+
+    let units = device.units();
+    if let Some(fs_xl) = fs_xl {
+        if !units.xl_fs.contains(&fs_xl) {
+            return Err(format!("{fs_xl:?} is not supported by {device:?}"));
+        }
+        config.fs_xl = fs_xl;
+    }
+    if let Some(fs_gy) = fs_gy {
+        if !units.gy_fs.contains(&fs_gy) {
+            return Err(format!("{fs_gy:?} is not supported by {device:?}"));
+        }
+        config.fs_gy = fs_gy;
+    }
+    if let Some(fs_hg) = fs_hg {
+        if !units.hg_fs.contains(&fs_hg) {
+            return Err(format!("{fs_hg:?} is not supported by {device:?}"));
+        }
+        config.fs_hg = Some(fs_hg);
+    }
+    if let Some(fs_dualc) = fs_dualc {
+        if !units.xl_fs.contains(&fs_dualc) {
+            return Err(format!("{fs_dualc:?} is not supported by {device:?}"));
+        }
+        config.fs_dualc = fs_dualc;
+    }
+    if let Some(fs_eis) = fs_eis {
+        if !units.gy_fs.contains(&fs_eis) {
+            return Err(format!("{fs_eis:?} is not supported by {device:?}"));
+        }
+        config.fs_eis = fs_eis;
+    }
+    for (slot, ext_sensor) in config.ext_sensors.iter_mut().zip(ext_sensors) {
+        if let Some((format, hz)) = ext_sensor {
+            *slot = Some(ExtSensorSlot::new(format, bdr(hz)?));
+        }
+    }
+
+    if let Some(freq_fine) = freq_fine {
+        config.time_base = config.time_base.with_freq_fine(freq_fine);
+    }
+
+    Ok(Some(Args {
+        config,
+        input: input.filter(|path| path != "-"),
+        input_format,
+        output_format,
+        analysis,
+    }))
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value '{value}' for {option}"))
+}
+
+fn parse_fs_xl(value: &str) -> Result<XlFullScale, String> {
+    match value.trim_end_matches('g') {
+        "2" => Ok(XlFullScale::G2),
+        "4" => Ok(XlFullScale::G4),
+        "8" => Ok(XlFullScale::G8),
+        "16" => Ok(XlFullScale::G16),
+        "32" => Ok(XlFullScale::G32),
+        _ => Err(format!("invalid accelerometer full scale '{value}'")),
+    }
+}
+
+fn parse_fs_gy(value: &str) -> Result<GyFullScale, String> {
+    match value.trim_end_matches("dps") {
+        "125" => Ok(GyFullScale::Dps125),
+        "250" => Ok(GyFullScale::Dps250),
+        "500" => Ok(GyFullScale::Dps500),
+        "1000" => Ok(GyFullScale::Dps1000),
+        "2000" => Ok(GyFullScale::Dps2000),
+        "4000" => Ok(GyFullScale::Dps4000),
+        _ => Err(format!("invalid gyroscope full scale '{value}'")),
+    }
+}
+
+fn parse_fs_hg(value: &str) -> Result<HgFullScale, String> {
+    match value.trim_end_matches('g') {
+        "32" => Ok(HgFullScale::G32),
+        "64" => Ok(HgFullScale::G64),
+        "80" => Ok(HgFullScale::G80),
+        "128" => Ok(HgFullScale::G128),
+        "256" => Ok(HgFullScale::G256),
+        "320" => Ok(HgFullScale::G320),
+        _ => Err(format!("invalid high-g full scale '{value}'")),
+    }
+}
+
+fn parse_ext_sensor(value: &str) -> Result<(usize, ExtSensorFormat, f32), String> {
+    let invalid = || format!("invalid sensor-hub slot '{value}', expected <0..3>:<lis2mdl|lps22df|raw>:<Hz>");
+    let mut fields = value.split(':');
+    let (Some(n), Some(format), Some(bdr), None) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+        return Err(invalid());
+    };
+
+    let n: usize = n.parse().ok().filter(|&n| n < 4).ok_or_else(invalid)?;
+    let format = match format.to_ascii_lowercase().as_str() {
+        "lis2mdl" => ExtSensorFormat::LIS2MDL,
+        "lps22df" => ExtSensorFormat::LPS22DF,
+        "raw" => ExtSensorFormat::Raw,
+        _ => return Err(invalid()),
+    };
+    let bdr = bdr.parse().map_err(|_| invalid())?;
+
+    Ok((n, format, bdr))
+}
+
+fn read_input(path: Option<&str>) -> Result<Vec<u8>, String> {
+    let mut input = Vec::new();
+
+    match path {
+        Some(path) => input = fs::read(path).map_err(|e| format!("{path}: {e}"))?,
+        None => {
+            io::stdin().read_to_end(&mut input).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(input)
+}
+
+/// Parses a hex dump: bytes separated by spaces, commas or new lines, with or
+/// without the `0x` prefix, or whole words written as 14 hex digits.
+/// Everything after a `#` or `//` on a line is ignored.
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let line = line.split("//").next().unwrap_or_default();
+
+        for token in line.split(|c: char| c.is_whitespace() || c == ',' || c == ';' || c == ':') {
+            let digits = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token);
+
+            if digits.is_empty() {
+                continue;
+            }
+
+            if digits.len() % 2 != 0 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(format!("line {}: invalid hex token '{token}'", n + 1));
+            }
+
+            for i in (0..digits.len()).step_by(2) {
+                bytes.push(u8::from_str_radix(&digits[i..i + 2], 16).unwrap());
+            }
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Splits a measurement into up to four values and their unit.
+fn measurement_values(measurement: &Measurement) -> (Vec<f32>, &'static str) {
+    match measurement {
+        Measurement::Acceleration(v) | Measurement::Gravity(v) => (vec![v.x, v.y, v.z], "m/s^2"),
+        Measurement::AngularRate(v) | Measurement::GyroBias(v) => (vec![v.x, v.y, v.z], "dps"),
+        Measurement::Temperature(t) => (vec![*t], "degC"),
+        Measurement::StepCount(steps) => (vec![*steps as f32], "steps"),
+        Measurement::Quaternion(q) => (vec![q.x, q.y, q.z, q.w], ""),
+        Measurement::MagneticField(v) => (vec![v.x, v.y, v.z], "uT"),
+        Measurement::Pressure { pressure, temperature } => (vec![*pressure, *temperature], "hPa/degC"),
+        Measurement::Unsupported => (Vec::new(), ""),
+    }
+}
+
+fn write_csv(line: &mut String, sample: &OutSlot, conf: &Config) -> std::fmt::Result {
+    let [raw_x, raw_y, raw_z] = sample.sensor_data.data;
+    let (values, unit) = measurement_values(&sample.to_si(conf));
+
+    write!(
+        line,
+        "{},{},{},{raw_x},{raw_y},{raw_z}",
+        sample.timestamp,
+        sample.timestamp_ns(&conf.time_base),
+        sample.sensor_tag
+    )?;
+
+    for i in 0..4 {
+        match values.get(i) {
+            Some(value) => write!(line, ",{value}")?,
+            None => line.push(','),
+        }
+    }
+
+    writeln!(line, ",{unit}")
+}
+
+fn write_jsonl(line: &mut String, sample: &OutSlot, conf: &Config) -> std::fmt::Result {
+    let [raw_x, raw_y, raw_z] = sample.sensor_data.data;
+
+    write!(
+        line,
+        "{{\"ticks\":{},\"time_ns\":{},\"sensor\":\"{}\",\"raw\":[{raw_x},{raw_y},{raw_z}]",
+        sample.timestamp,
+        sample.timestamp_ns(&conf.time_base),
+        sample.sensor_tag
+    )?;
+
+    match sample.to_si(conf) {
+        Measurement::Acceleration(v) | Measurement::Gravity(v) => {
+            write!(line, ",\"value\":{{\"x\":{},\"y\":{},\"z\":{}}},\"unit\":\"m/s^2\"", v.x, v.y, v.z)?
+        }
+        Measurement::AngularRate(v) | Measurement::GyroBias(v) => {
+            write!(line, ",\"value\":{{\"x\":{},\"y\":{},\"z\":{}}},\"unit\":\"dps\"", v.x, v.y, v.z)?
+        }
+        Measurement::Temperature(t) => write!(line, ",\"value\":{t},\"unit\":\"degC\"")?,
+        Measurement::StepCount(steps) => write!(line, ",\"value\":{steps},\"unit\":\"steps\"")?,
+        Measurement::Quaternion(q) => {
+            write!(line, ",\"value\":{{\"w\":{},\"x\":{},\"y\":{},\"z\":{}}}", q.w, q.x, q.y, q.z)?
+        }
+        Measurement::MagneticField(v) => {
+            write!(line, ",\"value\":{{\"x\":{},\"y\":{},\"z\":{}}},\"unit\":\"uT\"", v.x, v.y, v.z)?
+        }
+        Measurement::Pressure { pressure, temperature } => write!(
+            line,
+            ",\"value\":{{\"pressure\":{pressure},\"temperature\":{temperature}}},\"unit\":\"hPa/degC\""
+        )?,
+        Measurement::Unsupported => {}
+    }
+
+    writeln!(line, "}}")
+}
+
+fn print_summary(samples: &[OutSlot], odr_changes: &[OdrChange], stats: &FifoStats, errors: u32) {
+    eprintln!(
+        "{} words, {} samples, {errors} invalid words, {:.2} samples per accelerometer/gyroscope word",
+        stats.words,
+        samples.iter().filter(|s| s.sensor_tag != SensorType::DataLoss).count(),
+        stats.compression_ratio()
+    );
+
+    for sensor_type in (0..u8::MAX).map_while(|v| SensorType::try_from(v).ok()) {
+        let count = stats.samples(sensor_type);
+        let losses = samples
+            .iter()
+            .filter(|s| s.sensor_tag == SensorType::DataLoss)
+            .map(|s| s.sensor_data.to_data_loss())
+            .filter(|loss| loss.sensor_type == sensor_type && loss.cause == DataLossCause::TagCounter);
+        let (gaps, lost) = losses.fold((0, 0), |(n, lost), loss| (n + 1, lost + loss.lost_samples as u32));
+
+        if count > 0 {
+            eprint!("  {:<14} {count:>8} samples", sensor_type.to_string());
+            if gaps > 0 {
+                eprint!(", {gaps} gaps ({lost} missing samples)");
+            }
+            eprintln!();
+        }
+    }
+
+    if stats.gaps > 0 {
+        eprintln!(
+            "{} data losses ({} lost samples or time slots, {} overruns)",
+            stats.gaps, stats.lost_samples, stats.overruns
+        );
+    }
+
+    eprintln!("{} ODR changes", stats.odr_changes);
+
+    for change in &odr_changes[1..] {
+        eprintln!(
+            "  word {} at tick {}: xl {} Hz, gy {} Hz, vsens {} Hz",
+            change.word, change.timestamp, change.bdr_xl, change.bdr_gy, change.bdr_vsens
+        );
+    }
+}
+
+fn print_analysis(samples: &[OutSlot], conf: &Config) {
+    eprintln!("analysis:");
+
+    for sensor_type in (0..u8::MAX).map_while(|v| SensorType::try_from(v).ok()) {
+        let odr = analysis::odr(samples, conf, sensor_type);
+        let noise = analysis::noise(samples, conf, sensor_type);
+
+        if odr.is_none() && noise.is_none() {
+            continue;
+        }
+
+        eprintln!("  {sensor_type}");
+
+        if let Some(odr) = odr {
+            eprintln!(
+                "    ODR {:.4} Hz (nominal {} Hz, {:+.0} ppm), jitter {:.2} us RMS / {:.2} us max, {} dropped",
+                odr.effective_hz, odr.nominal_hz, odr.error_ppm, odr.jitter_rms_us, odr.jitter_max_us, odr.dropped
+            );
+        }
+
+        if let Some(noise) = noise {
+            let [mx, my, mz] = noise.mean;
+            let [rx, ry, rz] = noise.rms;
+            eprintln!("    mean {mx:.6} {my:.6} {mz:.6}, RMS noise {rx:.6} {ry:.6} {rz:.6}");
+        }
+
+        if sensor_type == SensorType::Gyroscope {
+            for point in analysis::allan_deviation(samples, conf, sensor_type) {
+                let [x, y, z] = point.adev;
+                eprintln!("    Allan deviation at {:.4} s: {x:.6} {y:.6} {z:.6} dps", point.tau);
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Per-sensor buffers of decoded samples.
+//!
+//! `Demux` replaces the `get_sensor_occurrence`/`extract_sensor` pair: the
+//! decoder (`FifoData::decode_into`) or the caller pushes each sample once into
+//! the ring buffer of its sensor, and each consumer drains its own sensor.
+
+use core::slice;
+
+use crate::{FifoData, MAX_SLOTS_PER_WORD, OutSlot, RawSlot, SensorType, Status};
+
+/// Number of `SensorType` values, decoder events and custom tags included.
+const SENSOR_TYPES: usize = SensorType::Custom3 as usize + 1;
+
+/// What to do with a sample pushed into a full buffer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OverflowPolicy {
+    DropOldest, // the oldest sample is overwritten
+    DropNewest, // the pushed sample is discarded
+}
+
+/// Statistics of the buffer of a sensor, returned by `Demux::stats`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DemuxStats {
+    pub high_water: usize, // highest number of buffered samples
+    pub dropped: u32,      // samples lost by overflow
+}
+
+#[derive(Clone, Copy)]
+struct Ring<const N: usize> {
+    buf: [OutSlot; N],
+    head: usize, // oldest sample
+    len: usize,
+    stats: DemuxStats,
+}
+
+impl<const N: usize> Ring<N> {
+    fn push(&mut self, slot: OutSlot, policy: OverflowPolicy) -> bool {
+        if self.len == N {
+            self.stats.dropped += 1;
+
+            if N == 0 || policy == OverflowPolicy::DropNewest {
+                return false;
+            }
+
+            self.buf[self.head] = slot;
+            self.head = (self.head + 1) % N;
+            return true;
+        }
+
+        self.buf[(self.head + self.len) % N] = slot;
+        self.len += 1;
+        self.stats.high_water = self.stats.high_water.max(self.len);
+        true
+    }
+
+    fn pop(&mut self) -> Option<OutSlot> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let slot = self.buf[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(slot)
+    }
+}
+
+/// One ring buffer of `N` samples per enabled `SensorType`.
+///
+/// Samples of the sensors not enabled are ignored. The buffers are statically
+/// allocated for the 28 sensor types (`SensorType::Gyroscope` to
+/// `SensorType::Custom3`): the size is about `28 * N * 12` bytes.
+#[derive(Clone)]
+pub struct Demux<const N: usize> {
+    rings: [Ring<N>; SENSOR_TYPES],
+    enabled: u32, // bit mask of the enabled sensor types
+    policy: OverflowPolicy,
+}
+
+impl<const N: usize> Demux<N> {
+    pub fn new(sensors: &[SensorType], policy: OverflowPolicy) -> Self {
+        let ring = Ring {
+            buf: [OutSlot::default(); N],
+            head: 0,
+            len: 0,
+            stats: DemuxStats::default(),
+        };
+
+        Demux {
+            rings: [ring; SENSOR_TYPES],
+            enabled: sensors.iter().fold(0, |mask, sensor_type| mask | 1 << *sensor_type as u32),
+            policy,
+        }
+    }
+
+    /// Returns true if the samples of the sensor are buffered.
+    pub fn is_enabled(&self, sensor_type: SensorType) -> bool {
+        self.enabled & 1 << sensor_type as u32 != 0
+    }
+
+    /// Buffers a sample; returns false if its sensor is not enabled or the sample
+    /// was discarded (`OverflowPolicy::DropNewest`).
+    pub fn push(&mut self, slot: OutSlot) -> bool {
+        self.is_enabled(slot.sensor_tag) && self.rings[slot.sensor_tag as usize].push(slot, self.policy)
+    }
+
+    /// Buffers the samples in order.
+    pub fn extend(&mut self, slots: &[OutSlot]) {
+        for slot in slots {
+            self.push(*slot);
+        }
+    }
+
+    /// Removes and returns the oldest sample of the sensor.
+    pub fn pop(&mut self, sensor_type: SensorType) -> Option<OutSlot> {
+        self.rings[sensor_type as usize].pop()
+    }
+
+    /// Returns the oldest sample of the sensor without removing it.
+    pub fn peek(&self, sensor_type: SensorType) -> Option<&OutSlot> {
+        let ring = &self.rings[sensor_type as usize];
+        (ring.len > 0).then(|| &ring.buf[ring.head])
+    }
+
+    /// Removes the samples of the sensor, oldest first.
+    pub fn drain(&mut self, sensor_type: SensorType) -> impl Iterator<Item = OutSlot> + '_ {
+        let ring = &mut self.rings[sensor_type as usize];
+        core::iter::from_fn(move || ring.pop())
+    }
+
+    /// Returns the number of buffered samples of the sensor.
+    pub fn len(&self, sensor_type: SensorType) -> usize {
+        self.rings[sensor_type as usize].len
+    }
+
+    pub fn is_empty(&self, sensor_type: SensorType) -> bool {
+        self.len(sensor_type) == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the buffer statistics of the sensor since `new` or the last `reset_stats`.
+    pub fn stats(&self, sensor_type: SensorType) -> DemuxStats {
+        self.rings[sensor_type as usize].stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        for ring in &mut self.rings {
+            ring.stats = DemuxStats::default();
+        }
+    }
+}
+
+impl FifoData {
+    /// Decodes the FIFO words like `decode_with_overrun`, pushing the samples
+    /// (and the `SensorType::DataLoss` slots) into `demux` instead of an output
+    /// array.
+    pub fn decode_into<const N: usize>(
+        &mut self,
+        demux: &mut Demux<N>,
+        fifo_raw_slot: &[RawSlot],
+        stream_size: u16,
+        fifo_ovr: bool,
+    ) -> Status {
+        let mut out = [OutSlot::default(); MAX_SLOTS_PER_WORD];
+        let mut out_size = 0;
+
+        if fifo_ovr {
+            self.decode_with_overrun(&mut out, &[], &mut out_size, 0, true);
+            demux.extend(&out[..out_size as usize]);
+        }
+
+        for raw_slot in fifo_raw_slot.iter().take(stream_size as usize) {
+            out_size = 0;
+
+            if self.decode_with_overrun(&mut out, slice::from_ref(raw_slot), &mut out_size, 1, false) == Status::Err {
+                return Status::Err;
+            }
+
+            demux.extend(&out[..out_size as usize]);
+        }
+
+        Status::Ok
+    }
+}
@@ -0,0 +1,365 @@
+use crate::{Bdr, Config, DEVICES, DeviceType, FifoData, OutSlot, RawSlot, SensorType, Status, Tag, TagMask, TagShift, TsDecimation, max};
+
+/// Generates the FIFO stream a sensor would produce for a set of samples.
+///
+/// The encoder is the mirror of `FifoData::decode`: samples are placed in the
+/// time slots of the fastest batched sensor, tagged with the 2-bit tag counter
+/// (and the parity bit on fifo_ver 0), anchored with TS words
+/// and, when compression is enabled, accelerometer and gyroscope samples are
+/// packed into 3x/2x compressed words whenever their differences fit.
+///
+/// Samples must be sorted by timestamp and lie on the time slots grid:
+/// accelerometer and gyroscope samples can only be compressed when they are
+/// spaced by exactly their batch data rate period.
+pub struct FifoEncoder {
+    device: DeviceType,
+    fifo_ver: u8,
+    compression: bool,
+    ts_decimation: TsDecimation,
+    bdr_hg: f32,
+    bdr_aux: f32,
+    dtime_xl: u32,
+    dtime_gy: u32,
+    dtime_min: u32,
+    started: bool,
+    ts_pending: bool,
+    slot_time: u32,
+    slot_index: u32,
+    tag_counter: u8,
+    xl: Channel,
+    gy: Channel,
+}
+
+struct Channel {
+    last_data: [i16; 3],
+    group: [[i16; 3]; 3],
+    group_len: usize,
+    nc_pending: bool, // next sample is written uncompressed (stream start, BDR change)
+}
+
+impl Default for Channel {
+    fn default() -> Self {
+        Channel {
+            last_data: [0; 3],
+            group: [[0; 3]; 3],
+            group_len: 0,
+            nc_pending: true,
+        }
+    }
+}
+
+impl FifoEncoder {
+    pub fn new(conf: &Config, compression: bool, ts_decimation: TsDecimation) -> Result<Self, Status> {
+        if conf.device.is_pattern_fifo() {
+            return Err(Status::Err);
+        }
+
+        let mut encoder = FifoEncoder {
+            device: conf.device,
+            fifo_ver: conf.device.fifo_ver(),
+            compression,
+            ts_decimation,
+            bdr_hg: conf.bdr_hg.hz(),
+            bdr_aux: conf.bdr_aux(),
+            dtime_xl: 0,
+            dtime_gy: 0,
+            dtime_min: 0,
+            started: false,
+            ts_pending: true,
+            slot_time: 0,
+            slot_index: 0,
+            tag_counter: 0,
+            xl: Channel::default(),
+            gy: Channel::default(),
+        };
+
+        encoder.set_bdr(conf.bdr_xl, conf.bdr_gy, conf.bdr_vsens)?;
+
+        Ok(encoder)
+    }
+
+    /// Encodes a batch of samples into `fifo_raw_slot` and returns the number of words written.
+    ///
+    /// The decoder state is carried across calls, so consecutive batches form a single stream.
+    pub fn encode(&mut self, samples: &[OutSlot], fifo_raw_slot: &mut [RawSlot]) -> Result<u16, Status> {
+        let mut n = 0;
+
+        for (i, sample) in samples.iter().enumerate() {
+            if i > 0 && sample.timestamp < samples[i - 1].timestamp {
+                return Err(Status::Err);
+            }
+
+            match sample.sensor_tag {
+                SensorType::Accelerometer | SensorType::Gyroscope if self.compression => {
+                    self.encode_compressed(samples, i, fifo_raw_slot, &mut n)?;
+                }
+                SensorType::Accelerometer | SensorType::Gyroscope => {
+                    let channel = self.channel(sample.sensor_tag);
+                    channel.last_data = sample.sensor_data.data;
+                    channel.nc_pending = false;
+                    self.write_axis(sample, Self::nc_tag(sample.sensor_tag), fifo_raw_slot, &mut n)?;
+                }
+                _ => {
+                    let tag = Self::tag_code(sample.sensor_tag, self.fifo_ver).ok_or(Status::Err)?;
+                    let mut payload = sample.sensor_data.to_bytes();
+
+                    // step counter and MLC results carry their own timestamp
+                    if sample.sensor_tag == SensorType::StepCounter || sample.sensor_tag == SensorType::MlcResult {
+                        payload[2..6].copy_from_slice(&sample.timestamp.to_le_bytes());
+                    }
+
+                    self.write(sample.timestamp, tag, payload, fifo_raw_slot, &mut n)?;
+                }
+            }
+        }
+
+        Ok(n)
+    }
+
+    /// Writes the ODRCHG word produced by the sensor when the batch data rates change at time `t`.
+    pub fn change_bdr(
+        &mut self,
+        t: u32,
+        bdr_xl: Bdr,
+        bdr_gy: Bdr,
+        bdr_vsens: Bdr,
+        fifo_raw_slot: &mut [RawSlot],
+    ) -> Result<u16, Status> {
+        let device = &DEVICES[self.fifo_ver as usize];
+        let code = |table: &[f32; 16], bdr: Bdr| bdr.index(self.device, table).map(|idx| idx as u8);
+
+        let mut payload = [0u8; 6];
+        payload[5] = code(&device.bdr_acc, bdr_xl)? | code(&device.bdr_gyr, bdr_gy)? << 4;
+        payload[3] = code(&device.bdr_vsens, bdr_vsens)?;
+
+        let mut n = 0;
+        self.write(t, Tag::Odrchg as u8, payload, fifo_raw_slot, &mut n)?;
+
+        self.set_bdr(bdr_xl, bdr_gy, bdr_vsens)?;
+        self.xl.nc_pending = true;
+        self.gy.nc_pending = true;
+        self.ts_pending = true;
+
+        Ok(n)
+    }
+
+    fn set_bdr(&mut self, bdr_xl: Bdr, bdr_gy: Bdr, bdr_vsens: Bdr) -> Result<(), Status> {
+        let device = &DEVICES[self.fifo_ver as usize];
+
+        bdr_xl.index(self.device, &device.bdr_acc)?;
+        bdr_gy.index(self.device, &device.bdr_gyr)?;
+        bdr_vsens.index(self.device, &device.bdr_vsens)?;
+
+        let (bdr_xl, bdr_gy, bdr_vsens) = (bdr_xl.hz(), bdr_gy.hz(), bdr_vsens.hz());
+        let bdr_max = max(max(max(max(bdr_xl, bdr_gy), bdr_vsens), self.bdr_hg), self.bdr_aux);
+
+        self.dtime_xl = device.dtime[FifoData::bdr_get_index(&device.bdr_acc, bdr_xl)];
+        self.dtime_gy = device.dtime[FifoData::bdr_get_index(&device.bdr_gyr, bdr_gy)];
+        self.dtime_min = device.dtime[FifoData::bdr_get_index(&device.bdr_acc, bdr_max)];
+
+        if self.dtime_min == 0 {
+            return Err(Status::Err);
+        }
+
+        Ok(())
+    }
+
+    fn encode_compressed(
+        &mut self,
+        samples: &[OutSlot],
+        i: usize,
+        fifo_raw_slot: &mut [RawSlot],
+        n: &mut u16,
+    ) -> Result<(), Status> {
+        let sample = &samples[i];
+        let dtime = if sample.sensor_tag == SensorType::Accelerometer { self.dtime_xl } else { self.dtime_gy };
+        let channel = self.channel(sample.sensor_tag);
+
+        if channel.group_len > 0 {
+            channel.group[channel.group_len] = sample.sensor_data.data;
+            channel.group_len += 1;
+
+            if channel.group_len == 3 {
+                channel.group_len = 0;
+                self.write_group(sample, fifo_raw_slot, n)?;
+            }
+
+            return Ok(());
+        }
+
+        // a group is opened only if the next two samples of the sensor are consecutive
+        let mut next = samples[i + 1..].iter().filter(|s| s.sensor_tag == sample.sensor_tag);
+        let consecutive = matches!(
+            (next.next(), next.next()),
+            (Some(s1), Some(s2)) if s1.timestamp == sample.timestamp.wrapping_add(dtime)
+                && s2.timestamp == sample.timestamp.wrapping_add(2 * dtime)
+        );
+
+        if consecutive && !channel.nc_pending {
+            channel.group[0] = sample.sensor_data.data;
+            channel.group_len = 1;
+            return Ok(());
+        }
+
+        channel.last_data = sample.sensor_data.data;
+        channel.nc_pending = false;
+        self.write_axis(sample, Self::nc_tag(sample.sensor_tag), fifo_raw_slot, n)
+    }
+
+    fn write_group(&mut self, last: &OutSlot, fifo_raw_slot: &mut [RawSlot], n: &mut u16) -> Result<(), Status> {
+        let channel = self.channel(last.sensor_tag);
+        let group = channel.group;
+        let mut diff = [[0i32; 3]; 3];
+        let mut prev = channel.last_data;
+
+        for (k, data) in group.iter().enumerate() {
+            for axis in 0..3 {
+                diff[k][axis] = data[axis] as i32 - prev[axis] as i32;
+            }
+            prev = *data;
+        }
+
+        channel.last_data = group[2];
+
+        let fits = |d: &[i32; 3], lo: i32, hi: i32| d.iter().all(|v| (lo..=hi).contains(v));
+        let xl = last.sensor_tag == SensorType::Accelerometer;
+
+        if diff.iter().all(|d| fits(d, -16, 15)) {
+            let mut payload = [0u8; 6];
+
+            for (k, d) in diff.iter().enumerate() {
+                let word = (d[0] as u16 & 0x1F) | (d[1] as u16 & 0x1F) << 5 | (d[2] as u16 & 0x1F) << 10;
+                payload[2 * k..2 * k + 2].copy_from_slice(&word.to_le_bytes());
+            }
+
+            let tag = if xl { Tag::XlCompressed3x } else { Tag::GyCompressed3x };
+            return self.write(last.timestamp, tag as u8, payload, fifo_raw_slot, n);
+        }
+
+        if fits(&diff[0], -128, 127) && fits(&diff[1], -128, 127) {
+            let mut payload = [0u8; 6];
+
+            for (k, d) in diff[..2].iter().enumerate() {
+                for axis in 0..3 {
+                    payload[3 * k + axis] = d[axis] as i8 as u8;
+                }
+            }
+
+            let tag = if xl { Tag::XlCompressed2x } else { Tag::GyCompressed2x };
+            self.write(last.timestamp, tag as u8, payload, fifo_raw_slot, n)?;
+        } else {
+            let (t2, t1) = if xl {
+                (Tag::XlUncompressedT2, Tag::XlUncompressedT1)
+            } else {
+                (Tag::GyUncompressedT2, Tag::GyUncompressedT1)
+            };
+            self.write(last.timestamp, t2 as u8, Self::axis_bytes(group[0]), fifo_raw_slot, n)?;
+            self.write(last.timestamp, t1 as u8, Self::axis_bytes(group[1]), fifo_raw_slot, n)?;
+        }
+
+        self.write(last.timestamp, Self::nc_tag(last.sensor_tag), Self::axis_bytes(group[2]), fifo_raw_slot, n)
+    }
+
+    fn write_axis(&mut self, sample: &OutSlot, tag: u8, fifo_raw_slot: &mut [RawSlot], n: &mut u16) -> Result<(), Status> {
+        self.write(sample.timestamp, tag, Self::axis_bytes(sample.sensor_data.data), fifo_raw_slot, n)
+    }
+
+    /// Appends a word in the time slot of `t`, preceded by a TS word when needed.
+    fn write(&mut self, t: u32, tag: u8, payload: [u8; 6], fifo_raw_slot: &mut [RawSlot], n: &mut u16) -> Result<(), Status> {
+        if !self.started || t != self.slot_time {
+            let elapsed = t.wrapping_sub(self.slot_time);
+
+            if self.started && (t < self.slot_time || !elapsed.is_multiple_of(self.dtime_min)) {
+                return Err(Status::Err);
+            }
+
+            let slots = if self.started { elapsed / self.dtime_min } else { 0 };
+            let decimation = self.ts_decimation.factor();
+            let decimated = decimation != 0
+                && (self.slot_index + slots) / decimation != self.slot_index / decimation;
+
+            self.tag_counter = (self.tag_counter + (slots & 0x03) as u8) & 0x03;
+            self.slot_index += slots;
+            self.slot_time = t;
+
+            // the tag counter cannot tell more than 3 slots apart: anchor with a TS word
+            if !self.started || self.ts_pending || slots > 3 || decimated {
+                let mut ts = [0u8; 6];
+                ts[..4].copy_from_slice(&t.to_le_bytes());
+                self.push(Tag::Ts as u8, ts, fifo_raw_slot, n)?;
+                self.ts_pending = false;
+            }
+
+            self.started = true;
+        }
+
+        self.push(tag, payload, fifo_raw_slot, n)
+    }
+
+    fn push(&self, tag: u8, payload: [u8; 6], fifo_raw_slot: &mut [RawSlot], n: &mut u16) -> Result<(), Status> {
+        let raw_slot = fifo_raw_slot.get_mut(*n as usize).ok_or(Status::Err)?;
+        let mut header = (tag << TagShift::Sensor as u8) & TagMask::Sensor as u8
+            | (self.tag_counter << TagShift::Counter as u8) & TagMask::Counter as u8;
+
+        // fifo_ver 0 uses odd parity on the tag byte
+        if self.fifo_ver == 0 && header.count_ones() & 1 == 0 {
+            header |= 0x01;
+        }
+
+        raw_slot.fifo_data_out[0] = header;
+        raw_slot.fifo_data_out[1..].copy_from_slice(&payload);
+        *n += 1;
+
+        Ok(())
+    }
+
+    fn channel(&mut self, sensor_type: SensorType) -> &mut Channel {
+        if sensor_type == SensorType::Accelerometer { &mut self.xl } else { &mut self.gy }
+    }
+
+    fn axis_bytes(data: [i16; 3]) -> [u8; 6] {
+        let mut payload = [0u8; 6];
+
+        for axis in 0..3 {
+            payload[2 * axis..2 * axis + 2].copy_from_slice(&data[axis].to_le_bytes());
+        }
+
+        payload
+    }
+
+    fn nc_tag(sensor_type: SensorType) -> u8 {
+        if sensor_type == SensorType::Accelerometer { Tag::Xl as u8 } else { Tag::Gy as u8 }
+    }
+
+    /// Returns the tag code of the non-compressed words of a sensor.
+    fn tag_code(sensor_type: SensorType, fifo_ver: u8) -> Option<u8> {
+        let tag = match sensor_type {
+            SensorType::Gyroscope => Tag::Gy,
+            SensorType::Accelerometer => Tag::Xl,
+            SensorType::Temperature => Tag::Temp,
+            SensorType::ExtSensor0 => Tag::ExtSens0,
+            SensorType::ExtSensor1 => Tag::ExtSens1,
+            SensorType::ExtSensor2 => Tag::ExtSens2,
+            SensorType::ExtSensor3 => Tag::ExtSens3,
+            SensorType::StepCounter => Tag::StepCounter,
+            SensorType::GameRv6x => Tag::GameRv,
+            SensorType::GeomRv6x => Tag::GeomRv,
+            SensorType::Rv9x => Tag::NormRv,
+            SensorType::GyroBias => Tag::GyroBias,
+            SensorType::Gravity => Tag::Gravity,
+            SensorType::MagCalib => Tag::MagCal,
+            SensorType::ExtSensorNack => Tag::ExtSensNack,
+            SensorType::MlcResult => Tag::MlcResult,
+            SensorType::MlcFilter => Tag::MlcFilter,
+            SensorType::MlcFeature => Tag::MlcFeature,
+            SensorType::DualAccel if fifo_ver < 2 => Tag::DualcXl,
+            SensorType::HighGAccel if fifo_ver == 2 => Tag::XlHg,
+            SensorType::EisGyro => Tag::EisGy,
+            _ => return None,
+        };
+
+        let code = tag.to_raw();
+
+        if code <= DEVICES[fifo_ver as usize].tag_valid_limit { Some(code) } else { None }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{Bdr, Config, Measurement, Vector3, max};
+
+/// Layout of the 6 bytes read by a sensor-hub slot.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ExtSensorFormat {
+    Raw,
+    /// Three little-endian i16 axes (X, Y, Z).
+    Magnetometer {
+        sensitivity: f32, // mgauss/LSB
+    },
+    /// 24-bit little-endian pressure followed by a 16-bit little-endian temperature.
+    Pressure {
+        pressure_sensitivity: f32,    // LSB/hPa
+        temperature_sensitivity: f32, // LSB/°C
+    },
+}
+
+impl ExtSensorFormat {
+    /// LIS2MDL magnetometer, OUTX_L_REG (68h) to OUTZ_H_REG (6Dh).
+    pub const LIS2MDL: Self = ExtSensorFormat::Magnetometer { sensitivity: 1.5 };
+    /// LPS22DF pressure sensor, PRESS_OUT_XL (28h) to TEMP_OUT_H (2Ch).
+    pub const LPS22DF: Self = ExtSensorFormat::Pressure {
+        pressure_sensitivity: 4096.0,
+        temperature_sensitivity: 100.0,
+    };
+
+    /// Converts the payload of a sensor-hub word.
+    pub fn decode(&self, raw: &[u8; 6]) -> Measurement {
+        match *self {
+            ExtSensorFormat::Raw => Measurement::Unsupported,
+            ExtSensorFormat::Magnetometer { sensitivity } => {
+                let axis = |i: usize| i16::from_le_bytes([raw[i], raw[i + 1]]);
+                // 1 mgauss = 0.1 µT
+                Measurement::MagneticField(Vector3::scale([axis(0), axis(2), axis(4)], sensitivity / 10.0))
+            }
+            ExtSensorFormat::Pressure {
+                pressure_sensitivity,
+                temperature_sensitivity,
+            } => {
+                // sign-extend the 24-bit value
+                let pressure = i32::from_le_bytes([0, raw[0], raw[1], raw[2]]) >> 8;
+                let temperature = i16::from_le_bytes([raw[3], raw[4]]);
+
+                Measurement::Pressure {
+                    pressure: pressure as f32 / pressure_sensitivity,
+                    temperature: temperature as f32 / temperature_sensitivity,
+                }
+            }
+        }
+    }
+}
+
+/// Sensor-hub slot (SLV0 to SLV3), batched with the `Tag::ExtSens0` to `Tag::ExtSens3` tags.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ExtSensorSlot {
+    pub format: ExtSensorFormat,
+    pub bdr: Bdr, // batch data rate of the slot, used to space its timestamps
+}
+
+impl ExtSensorSlot {
+    pub const fn new(format: ExtSensorFormat, bdr: Bdr) -> Self {
+        ExtSensorSlot { format, bdr }
+    }
+}
+
+impl Config {
+    /// Returns the highest batch data rate of the sensor-hub slots.
+    pub(crate) fn bdr_ext(&self) -> f32 {
+        self.ext_sensors.iter().flatten().fold(0.0, |bdr, slot| max(bdr, slot.bdr.hz()))
+    }
+}
@@ -0,0 +1,231 @@
+//! C ABI mirroring the `st_fifo` library of the STMems_Standard_C_drivers
+//! (see `include/st_fifo.h`).
+//!
+//! Like the C library, the decoder state is global: `st_fifo_init` resets it
+//! and the functions must not be called concurrently.
+
+use core::cell::UnsafeCell;
+use core::slice;
+
+use crate::{Bdr, Config, DeviceType, FifoData, MAX_SLOTS_PER_WORD, OutSlot, RawSlot, Status, sort};
+
+const ST_FIFO_OK: i32 = Status::Ok as i32;
+const ST_FIFO_ERR: i32 = Status::Err as i32;
+
+/// `st_fifo_conf`: device (`st_fifo_device`) and batch data rates in Hz.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StFifoConf {
+    pub device: i32, // st_fifo_device
+    pub bdr_xl: f32,
+    pub bdr_gy: f32,
+    pub bdr_vsens: f32,
+}
+
+/// `st_fifo_out_slot`: the 6 bytes of `sensor_data` are the union of the C
+/// structure, seen as three little-endian words.
+#[repr(C)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct StFifoOutSlot {
+    pub timestamp: u32,
+    pub sensor_tag: i32, // st_fifo_sensor_type
+    pub sensor_data: [i16; 3],
+}
+
+impl From<&OutSlot> for StFifoOutSlot {
+    fn from(value: &OutSlot) -> Self {
+        StFifoOutSlot {
+            timestamp: value.timestamp,
+            sensor_tag: value.sensor_tag as i32,
+            sensor_data: value.sensor_data.data,
+        }
+    }
+}
+
+struct Decoder {
+    fifo: FifoData,
+    report_events: bool,
+}
+
+struct Instance(UnsafeCell<Option<Decoder>>);
+
+// SAFETY: the C API is not reentrant, callers serialize the accesses.
+unsafe impl Sync for Instance {}
+
+static FIFO: Instance = Instance(UnsafeCell::new(None));
+
+/// Panic handler of the static library on a microcontroller, where std does not
+/// provide one.
+#[cfg(target_os = "none")]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
+
+/// Initializes the decoder; returns `ST_FIFO_ERR` if the device is unknown or
+/// a batch data rate is not supported by the device.
+///
+/// # Safety
+/// - `conf` is a valid pointer to a `st_fifo_conf`
+/// - no other `st_fifo_*` function runs at the same time
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn st_fifo_init(conf: *const StFifoConf) -> i32 {
+    if conf.is_null() {
+        return ST_FIFO_ERR;
+    }
+
+    let conf = unsafe { &*conf };
+    let Ok(device) = u8::try_from(conf.device).map_err(|_| ()).and_then(DeviceType::try_from) else {
+        return ST_FIFO_ERR;
+    };
+    let bdr = |hz: f32| Bdr::from_hz(device, hz);
+    let (Ok(bdr_xl), Ok(bdr_gy), Ok(bdr_vsens)) = (bdr(conf.bdr_xl), bdr(conf.bdr_gy), bdr(conf.bdr_vsens)) else {
+        return ST_FIFO_ERR;
+    };
+
+    let config = Config {
+        bdr_xl,
+        bdr_gy,
+        bdr_vsens,
+        ..Config::new(device)
+    };
+
+    match FifoData::init(&config) {
+        Ok(fifo) => {
+            unsafe { *FIFO.0.get() = Some(Decoder { fifo, report_events: false }) };
+            ST_FIFO_OK
+        }
+        Err(_) => ST_FIFO_ERR,
+    }
+}
+
+/// Not in the C library: when `report_events` is not 0, `st_fifo_decode` also
+/// outputs the `DataLoss` and `BdrChange` slots, up to `MAX_SLOTS_PER_WORD` per
+/// word. `st_fifo_init` turns them off, so that up to 3 slots per word are
+/// written as in the C library.
+///
+/// Returns `ST_FIFO_ERR` if `st_fifo_init` was not called.
+///
+/// # Safety
+/// No other `st_fifo_*` function runs at the same time.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn st_fifo_set_report_events(report_events: u8) -> i32 {
+    match unsafe { &mut *FIFO.0.get() } {
+        Some(decoder) => {
+            decoder.report_events = report_events != 0;
+            ST_FIFO_OK
+        }
+        None => ST_FIFO_ERR,
+    }
+}
+
+/// Decodes `stream_size` FIFO words into `fifo_out_slot` and stores the number
+/// of decoded samples in `out_slot_size`.
+///
+/// # Safety
+/// - `fifo_raw_slot` points to `stream_size` words
+/// - `fifo_out_slot` has room for the decoded samples: up to 3 per word, or
+///   `MAX_SLOTS_PER_WORD` after `st_fifo_set_report_events`
+/// - `out_slot_size` is a valid pointer
+/// - no other `st_fifo_*` function runs at the same time
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn st_fifo_decode(
+    fifo_out_slot: *mut StFifoOutSlot,
+    fifo_raw_slot: *const RawSlot,
+    out_slot_size: *mut u16,
+    stream_size: u16,
+) -> i32 {
+    let Some(Decoder { fifo, report_events }) = (unsafe { &mut *FIFO.0.get() }) else {
+        return ST_FIFO_ERR;
+    };
+
+    if fifo_out_slot.is_null() || fifo_raw_slot.is_null() || out_slot_size.is_null() {
+        return ST_FIFO_ERR;
+    }
+
+    let raw = unsafe { slice::from_raw_parts(fifo_raw_slot, stream_size as usize) };
+    let mut n = 0;
+    let mut status = ST_FIFO_OK;
+
+    // one word at a time, so that no buffer of the whole stream is needed
+    for raw_slot in raw.chunks(1) {
+        let mut out = [OutSlot::default(); MAX_SLOTS_PER_WORD];
+        let mut out_size = 0;
+
+        let decoded = if *report_events {
+            fifo.decode_with_overrun(&mut out, raw_slot, &mut out_size, 1, false)
+        } else {
+            fifo.decode(&mut out, raw_slot, &mut out_size, 1)
+        };
+
+        if decoded == Status::Err {
+            status = ST_FIFO_ERR;
+            break;
+        }
+
+        for slot in &out[..out_size as usize] {
+            unsafe { fifo_out_slot.add(n).write(slot.into()) };
+            n += 1;
+        }
+    }
+
+    unsafe { out_slot_size.write(n as u16) };
+
+    status
+}
+
+/// Sorts the decoded samples by timestamp (stable).
+///
+/// # Safety
+/// `fifo_out_slot` points to `out_slot_size` samples.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn st_fifo_sort(fifo_out_slot: *mut StFifoOutSlot, out_slot_size: u16) {
+    if fifo_out_slot.is_null() {
+        return;
+    }
+
+    let out = unsafe { slice::from_raw_parts_mut(fifo_out_slot, out_slot_size as usize) };
+    sort::stable_sort_by_key(out, |slot| slot.timestamp);
+}
+
+/// Returns the number of samples of `sensor_type`.
+///
+/// # Safety
+/// `fifo_out_slot` points to `out_slot_size` samples.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn st_fifo_get_sensor_occurrence(
+    fifo_out_slot: *const StFifoOutSlot,
+    out_slot_size: u16,
+    sensor_type: i32,
+) -> u16 {
+    if fifo_out_slot.is_null() {
+        return 0;
+    }
+
+    let out = unsafe { slice::from_raw_parts(fifo_out_slot, out_slot_size as usize) };
+    out.iter().filter(|slot| slot.sensor_tag == sensor_type).count() as u16
+}
+
+/// Copies the samples of `sensor_type` to `sensor_out_slot`.
+///
+/// # Safety
+/// - `fifo_out_slot` points to `out_slot_size` samples
+/// - `sensor_out_slot` has room for the samples of `sensor_type` (see
+///   `st_fifo_get_sensor_occurrence`)
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn st_fifo_extract_sensor(
+    sensor_out_slot: *mut StFifoOutSlot,
+    fifo_out_slot: *const StFifoOutSlot,
+    out_slot_size: u16,
+    sensor_type: i32,
+) {
+    if sensor_out_slot.is_null() || fifo_out_slot.is_null() {
+        return;
+    }
+
+    let out = unsafe { slice::from_raw_parts(fifo_out_slot, out_slot_size as usize) };
+
+    for (i, slot) in out.iter().filter(|slot| slot.sensor_tag == sensor_type).enumerate() {
+        unsafe { sensor_out_slot.add(i).write(*slot) };
+    }
+}
@@ -0,0 +1,35 @@
+/// IEEE 754 half-precision floating point value, as stored in the FIFO by the SFLP and MLC.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct F16(pub u16);
+
+impl F16 {
+    pub fn to_f32(self) -> f32 {
+        let sign = ((self.0 & 0x8000) as u32) << 16;
+        let exp = ((self.0 >> 10) & 0x1F) as u32;
+        let mant = (self.0 & 0x03FF) as u32;
+
+        let bits = match (exp, mant) {
+            // signed zero
+            (0, 0) => sign,
+            // subnormal: normalize the mantissa
+            (0, _) => {
+                let shift = mant.leading_zeros() - 21;
+                let mant = (mant << shift) & 0x03FF;
+                sign | ((113 - shift) << 23) | (mant << 13)
+            }
+            // infinity and NaN
+            (0x1F, _) => sign | 0x7F80_0000 | (mant << 13),
+            _ => sign | ((exp + 112) << 23) | (mant << 13),
+        };
+
+        f32::from_bits(bits)
+    }
+}
+
+impl From<F16> for f32 {
+    fn from(value: F16) -> Self {
+        value.to_f32()
+    }
+}
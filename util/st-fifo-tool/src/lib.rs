@@ -1,15 +1,58 @@
 #![no_std]
 
+// on the hosted targets std also provides the panic handler of the staticlib
+// and cdylib crate types
+#[cfg(any(feature = "std", not(target_os = "none")))]
+extern crate std;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+mod bdr;
+mod demux;
+mod encoder;
+mod ext_sensor;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod half;
+mod pattern;
+mod payload;
+pub mod planner;
+mod reconstruct;
 mod sensor_data;
+mod sflp;
+mod snapshot;
+mod sort;
+mod stats;
+mod sync;
+mod tag_decoder;
+mod time_base;
+mod units;
 
 use core::fmt;
-use sensor_data::*;
-
-pub static DEVICES: [Device; 2] = [
+use core::time::Duration;
+pub use bdr::*;
+pub use demux::*;
+pub use encoder::*;
+pub use ext_sensor::*;
+pub use half::*;
+pub use pattern::*;
+pub use payload::*;
+use reconstruct::HostAnchors;
+pub use sensor_data::*;
+pub use sflp::*;
+pub use snapshot::SNAPSHOT_SIZE;
+pub use stats::*;
+pub use sync::*;
+pub use tag_decoder::*;
+pub use time_base::*;
+pub use units::*;
+
+pub static DEVICES: [Device; 3] = [
     Device {
         bdr_acc: [0.0, 13.0, 26.0, 52.0, 104.0, 208.0, 416.0, 833.0, 1666.0, 3333.0, 6666.0, 1.625, 0.0, 0.0, 0.0, 0.0],
         bdr_gyr: [0.0, 13.0, 26.0, 52.0, 104.0, 208.0, 416.0, 833.0, 1666.0, 3333.0, 6666.0, 0.0, 0.0, 0.0, 0.0, 0.0],
         bdr_vsens: [0.0, 13.0, 26.0, 52.0, 104.0, 208.0, 416.0, 0.0, 0.0, 0.0, 0.0, 1.625, 0.0, 0.0, 0.0, 0.0],
+        bdr_hg: [0.0; 16],
         dtime: [0, 3072, 1536, 768, 384, 192, 96, 48, 24, 12, 6, 24576, 0, 0, 0, 0],
         tag_valid_limit: 0x19,
     },
@@ -17,11 +60,31 @@ pub static DEVICES: [Device; 2] = [
         bdr_acc: [0.0, 1.875, 7.5, 15.0, 30.0, 60.0, 120.0, 240.0, 480.0, 960.0, 1920.0, 3840.0, 7680.0, 0.0, 0.0, 0.0],
         bdr_gyr: [0.0, 1.875, 7.5, 15.0, 30.0, 60.0, 120.0, 240.0, 480.0, 960.0, 1920.0, 3840.0, 7680.0, 0.0, 0.0, 0.0],
         bdr_vsens: [0.0, 1.875, 7.5, 15.0, 30.0, 60.0, 120.0, 240.0, 480.0, 960.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        bdr_hg: [0.0; 16],
+        dtime: [0, 24576, 6144, 3072, 1536, 768, 384, 192, 96, 48, 24, 12, 6, 0, 0, 0],
+        tag_valid_limit: 0x1E,
+    },
+    Device {
+        bdr_acc: [0.0, 1.875, 7.5, 15.0, 30.0, 60.0, 120.0, 240.0, 480.0, 960.0, 1920.0, 3840.0, 7680.0, 0.0, 0.0, 0.0],
+        bdr_gyr: [0.0, 1.875, 7.5, 15.0, 30.0, 60.0, 120.0, 240.0, 480.0, 960.0, 1920.0, 3840.0, 7680.0, 0.0, 0.0, 0.0],
+        bdr_vsens: [0.0, 1.875, 7.5, 15.0, 30.0, 60.0, 120.0, 240.0, 480.0, 960.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        bdr_hg: [0.0, 0.0, 0.0, 480.0, 960.0, 1920.0, 3840.0, 7680.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
         dtime: [0, 24576, 6144, 3072, 1536, 768, 384, 192, 96, 48, 24, 12, 6, 0, 0, 0],
+        // same codes as fifo_ver 1, 0x1D being XlHg (Tag::to_raw) instead of DualcXl
         tag_valid_limit: 0x1E,
     },
 ];
 
+/// Channels timestamped on their own period: sensor-hub slots 0 to 3,
+/// dual-channel accelerometer, EIS gyroscope and temperature.
+const AUX_CHANNELS: usize = 7;
+
+/// Maximum number of output slots of a FIFO word with
+/// `FifoData::decode_with_overrun`: the 3 samples of a compressed word and the
+/// `SensorType::DataLoss` slot of a tag-counter gap. The FIFO_OVR flag takes one
+/// more slot. `FifoData::decode` outputs up to 3 slots per word.
+pub const MAX_SLOTS_PER_WORD: usize = 4;
+
 pub fn max<F: PartialOrd>(a: F, b: F) -> F {
     if a > b { a } else { b }
 }
@@ -39,6 +102,11 @@ pub struct FifoData {
     dtime_min: u32,
     dtime_xl_old: u32,
     dtime_gy_old: u32,
+    bdr: BdrChangeData, // accelerometer, gyroscope and virtual sensor rates in effect
+    bdr_hg: f32,
+    bdr_aux: f32,
+    dtime_aux: [u32; AUX_CHANNELS],
+    last_timestamp_aux: [Option<u32>; AUX_CHANNELS],
     timestamp: u32,
     last_timestamp_xl: u32,
     last_timestamp_gy: u32,
@@ -46,22 +114,92 @@ pub struct FifoData {
     bdr_chg_gy_flag: u8,
     last_data_xl: [i16; 3],
     last_data_gy: [i16; 3],
+    gap_last: [Option<u32>; 2],   // last accelerometer/gyroscope timestamp, for the gap detection
+    gap_dtime_old: [u32; 2],      // accelerometer/gyroscope period before an ODR change
+    ts_synced: bool,
+    ts_last: u32,       // timestamp of the last TS word
+    ts_correction: i32, // last TS word minus the timestamp predicted by the tag counter
+    ts_drift: u32,      // last correction smaller than half a time slot, in ticks
+    ts_decimation: TsDecimation,
+    time_base: TimeBase,
+    anchors: HostAnchors,
+    custom_tags: TagRegistry,
+    stats: FifoStats,
 }
 
 impl FifoData {
 
     pub fn init(conf: &Config) -> Result<Self, Status> {
-        let bdr_xl = conf.bdr_xl;
-        let bdr_gy = conf.bdr_gy;
-        let bdr_vsens = conf.bdr_vsens;
+        if conf.device.is_pattern_fifo() {
+            return Err(Status::Err);
+        }
+
+        let device = &DEVICES[conf.device.fifo_ver() as usize];
+
+        conf.bdr_xl.index(conf.device, &device.bdr_acc)?;
+        conf.bdr_gy.index(conf.device, &device.bdr_gyr)?;
+        conf.bdr_vsens.index(conf.device, &device.bdr_vsens)?;
+        conf.bdr_hg.index(conf.device, &device.bdr_hg)?;
+
+        for slot in conf.ext_sensors.iter().flatten() {
+            slot.bdr.index(conf.device, &device.bdr_acc)?;
+        }
+
+        // DualcXl is only batched by the fifo_ver 1 parts (the fifo_ver 2 high-g
+        // parts use its code for XlHg), EisGy by the fifo_ver 1 and 2 parts
+        let fifo_ver = conf.device.fifo_ver();
+        if (fifo_ver != 1 && !conf.bdr_dualc.is_off()) || (fifo_ver == 0 && !conf.bdr_eis.is_off()) {
+            return Err(Status::Err);
+        }
+
+        conf.bdr_dualc.index(conf.device, &device.bdr_acc)?;
+        conf.bdr_eis.index(conf.device, &device.bdr_gyr)?;
+        conf.bdr_temp.index(conf.device, &device.bdr_acc)?;
+
+        if !conf.bdr_temp.is_temp_rate(conf.device) {
+            return Err(Status::Err);
+        }
+
+        // the registered tags must be 5-bit codes unknown to the device
+        let known = |tag: u8| tag <= device.tag_valid_limit && Tag::from_raw(tag, conf.device.fifo_ver()).is_ok();
+        if conf.custom_tags.tags().any(|tag| tag > TagMask::Sensor as u8 >> TagShift::Sensor as u8 || known(tag)) {
+            return Err(Status::Err);
+        }
+
+        let bdr_xl = conf.bdr_xl.hz();
+        let bdr_gy = conf.bdr_gy.hz();
+        let bdr_vsens = conf.bdr_vsens.hz();
+        let bdr_hg = conf.bdr_hg.hz();
+        let bdr_aux = conf.bdr_aux();
         let bdr_max = max(bdr_xl, bdr_gy);
         let bdr_max = max(bdr_max, bdr_vsens);
-
-        if bdr_xl < 0.0 || bdr_gy < 0.0 || bdr_vsens < 0.0 {
+        let bdr_max = max(bdr_max, bdr_hg);
+        let bdr_max = max(bdr_max, bdr_aux);
+
+        let units = conf.device.units();
+        if !units.xl_fs.contains(&conf.fs_xl)
+            || !units.gy_fs.contains(&conf.fs_gy)
+            || !units.xl_fs.contains(&conf.fs_dualc)
+            || !units.gy_fs.contains(&conf.fs_eis)
+        {
             return Err(Status::Err);
         }
 
-        let fifo_ver = if conf.device < DeviceType::Lsm6dsv { 0 } else { 1 }; 
+        match conf.fs_hg {
+            Some(fs_hg) if !units.hg_fs.contains(&fs_hg) => return Err(Status::Err),
+            None if bdr_hg > 0.0 => return Err(Status::Err),
+            _ => {}
+        }
+
+        let mut dtime_aux = [0; AUX_CHANNELS];
+        let [ext0, ext1, ext2, ext3] = conf.ext_sensors.map(|slot| slot.map_or(Bdr::Off, |slot| slot.bdr));
+        let bdr_aux_channels = [ext0, ext1, ext2, ext3, conf.bdr_dualc, conf.bdr_eis, conf.bdr_temp];
+
+        for (dtime, bdr) in dtime_aux.iter_mut().zip(bdr_aux_channels) {
+            if !bdr.is_off() {
+                *dtime = DEVICES[fifo_ver as usize].dtime[FifoData::bdr_get_index(&DEVICES[fifo_ver as usize].bdr_acc, bdr.hz())];
+            }
+        }
 
         let sensor_data = FifoData {
             fifo_ver,
@@ -71,6 +209,11 @@ impl FifoData {
             dtime_min: DEVICES[fifo_ver as usize].dtime[FifoData::bdr_get_index(&DEVICES[fifo_ver as usize].bdr_acc, bdr_max)],
             dtime_xl_old: DEVICES[fifo_ver as usize].dtime[FifoData::bdr_get_index(&DEVICES[fifo_ver as usize].bdr_acc, bdr_xl)],
             dtime_gy_old: DEVICES[fifo_ver as usize].dtime[FifoData::bdr_get_index(&DEVICES[fifo_ver as usize].bdr_gyr, bdr_gy)],
+            bdr: BdrChangeData { xl: conf.bdr_xl, gy: conf.bdr_gy, vsens: conf.bdr_vsens },
+            bdr_hg,
+            bdr_aux,
+            dtime_aux,
+            last_timestamp_aux: [None; AUX_CHANNELS],
             timestamp: 0,
             last_timestamp_xl: 0,
             last_timestamp_gy: 0,
@@ -78,11 +221,27 @@ impl FifoData {
             bdr_chg_gy_flag: 0,
             last_data_xl: [0; 3],
             last_data_gy: [0; 3],
+            gap_last: [None; 2],
+            gap_dtime_old: [0; 2],
+            ts_synced: false,
+            ts_last: 0,
+            ts_correction: 0,
+            ts_drift: 0,
+            ts_decimation: conf.ts_decimation,
+            time_base: conf.time_base,
+            anchors: HostAnchors::default(),
+            custom_tags: conf.custom_tags,
+            stats: FifoStats::default(),
         };
 
         Ok(sensor_data)
     }
 
+    /// Decodes the FIFO words into samples, up to 3 slots per word.
+    ///
+    /// Only the samples are output: the data losses are counted in the
+    /// statistics and the ODRCHG words update the timing, see
+    /// `decode_with_overrun` for the slots reporting them.
     pub fn decode(
         &mut self,
         fifo_out_slot: &mut [OutSlot],
@@ -90,17 +249,108 @@ impl FifoData {
         out_slot_size: &mut u16,
         stream_size: u16,
     ) -> Status {
+        self.decode_words(fifo_out_slot, fifo_raw_slot, out_slot_size, stream_size, None, &mut |_, _| false)
+    }
+
+    /// Decodes the FIFO words like `decode`, given the FIFO_OVR flag read from the
+    /// sensor together with the FIFO level, and reports the events of the stream.
+    ///
+    /// Lost data is reported with `SensorType::DataLoss` slots (see
+    /// `SensorData::to_data_loss`): `fifo_out_slot` must have room for them too.
+    /// They are emitted when:
+    /// - `fifo_ovr` is set: the oldest words were overwritten, the number of lost
+    ///   samples is unknown;
+    /// - an accelerometer or gyroscope sample comes later than its period, i.e. the
+    ///   tag counter advanced more than expected (a read was skipped);
+    /// - a TS word is later than the timestamp predicted by the tag counter, i.e.
+    ///   the 2-bit counter wrapped around during a loss.
+    ///
+    /// An ODRCHG word gives a `SensorType::BdrChange` slot with the new rates
+    /// (see `SensorData::to_bdr_change`), timed at the word. The words with a tag
+    /// registered in `Config::custom_tags` are skipped, see `decode_custom`.
+    ///
+    /// A word takes up to `MAX_SLOTS_PER_WORD` slots: the room for the `DataLoss`
+    /// slot of an accelerometer or gyroscope word is reserved before the word is
+    /// decoded, even if no gap is found.
+    ///
+    /// Returns `Status::Err` on an invalid word or when `fifo_out_slot` has no room
+    /// for the slots of a word (`out_slot_size` holds the slots decoded so far).
+    pub fn decode_with_overrun(
+        &mut self,
+        fifo_out_slot: &mut [OutSlot],
+        fifo_raw_slot: &[RawSlot],
+        out_slot_size: &mut u16,
+        stream_size: u16,
+        fifo_ovr: bool,
+    ) -> Status {
+        self.decode_words(fifo_out_slot, fifo_raw_slot, out_slot_size, stream_size, Some(fifo_ovr), &mut |_, _| false)
+    }
+
+    /// Decodes the FIFO words like `decode`, passing the words with a tag
+    /// registered in `Config::custom_tags` to `decoder`: `sink` receives its
+    /// samples with the timing context of their word, in FIFO order.
+    pub fn decode_custom<D: TagDecoder>(
+        &mut self,
+        fifo_out_slot: &mut [OutSlot],
+        fifo_raw_slot: &[RawSlot],
+        out_slot_size: &mut u16,
+        stream_size: u16,
+        decoder: &mut D,
+        mut sink: impl FnMut(&TagContext, D::Sample),
+    ) -> Status {
+        let mut custom = |payload: &[u8; 6], ctx: &TagContext| match decoder.decode(payload, ctx) {
+            Some(sample) => {
+                sink(ctx, sample);
+                true
+            }
+            None => false,
+        };
+
+        self.decode_words(fifo_out_slot, fifo_raw_slot, out_slot_size, stream_size, None, &mut custom)
+    }
+
+    /// Decodes the FIFO words, passing the words with a registered tag to
+    /// `custom`, which returns whether the word gave a sample. The `DataLoss` and
+    /// `BdrChange` slots are output when `fifo_ovr` is given.
+    fn decode_words(
+        &mut self,
+        fifo_out_slot: &mut [OutSlot],
+        fifo_raw_slot: &[RawSlot],
+        out_slot_size: &mut u16,
+        stream_size: u16,
+        fifo_ovr: Option<bool>,
+        custom: &mut dyn FnMut(&[u8; 6], &TagContext) -> bool,
+    ) -> Status {
+        let events = fifo_ovr.is_some();
         let mut j = 0;
 
-        for i in 0..stream_size as usize {
-            let tag = (fifo_raw_slot[i].fifo_data_out[0] & TagMask::Sensor as u8) >> TagShift::Sensor as u8;
-            let tag_counter = (fifo_raw_slot[i].fifo_data_out[0] & TagMask::Counter as u8) >> TagShift::Counter as u8;
+        if fifo_ovr == Some(true) {
+            if fifo_out_slot.is_empty() {
+                *out_slot_size = 0;
+                return Status::Err;
+            }
+
+            // the timestamp is not reliable until the next TS word
+            self.stats.overruns += 1;
+            self.gap_last = [None; 2];
+            self.ts_synced = false;
+            j = self.push_data_loss(fifo_out_slot, j, self.timestamp, DataLossCause::Overrun, SensorType::None, 0, events);
+            *out_slot_size = j as u16;
+        }
+
+        for raw_slot in fifo_raw_slot.iter().take(stream_size as usize) {
+            let j_start = j;
 
-            if self.fifo_ver == 0 && FifoData::has_even_parity(fifo_raw_slot[i].fifo_data_out[0]) {
+            let tag = (raw_slot.fifo_data_out[0] & TagMask::Sensor as u8) >> TagShift::Sensor as u8;
+            let tag_counter = (raw_slot.fifo_data_out[0] & TagMask::Counter as u8) >> TagShift::Counter as u8;
+
+            if self.fifo_ver == 0 && FifoData::has_even_parity(raw_slot.fifo_data_out[0]) {
                 return Status::Err;
             }
 
-            if !self.is_tag_valid(tag) {
+            let custom_tag = self.custom_tags.get(tag);
+
+            if !self.is_tag_valid(tag) && custom_tag.is_none() {
                 return Status::Err;
             }
 
@@ -111,20 +361,49 @@ impl FifoData {
                     tag_counter - self.tag_counter_old
                 };
 
-                self.timestamp += self.dtime_min * diff_tag_counter as u32;
+                self.timestamp = self.timestamp.wrapping_add(self.dtime_min * diff_tag_counter as u32);
+            }
+
+            if let Some(sensor_type) = custom_tag {
+                self.stats.words += 1;
+
+                let ctx = TagContext {
+                    tag,
+                    sensor_type,
+                    tag_counter,
+                    fifo_ver: self.fifo_ver,
+                    timestamp: self.timestamp,
+                    dtime_min: self.dtime_min,
+                };
+
+                if custom(raw_slot.fifo_data_out[1..7].try_into().unwrap(), &ctx) {
+                    self.stats.samples[sensor_type as usize] += 1;
+                }
+
+                self.tag_counter_old = tag_counter;
+                continue;
             }
 
-            let tag = Tag::try_from(tag).map_err(|_| Status::Err).unwrap();
+            let Ok(tag) = Tag::from_raw(tag, self.fifo_ver) else {
+                return Status::Err;
+            };
+
+            self.stats.words += 1;
 
             if tag == Tag::Odrchg {
-                let bdr_acc_cfg = (fifo_raw_slot[i].fifo_data_out[6] & BdrMask::Xl as u8) >> BdrShift::Xl as u8;
-                let bdr_gyr_cfg = (fifo_raw_slot[i].fifo_data_out[6] & BdrMask::Gy as u8) >> BdrShift::Gy as u8;
-                let bdr_vsens_cfg = (fifo_raw_slot[i].fifo_data_out[4] & BdrMask::Vsens as u8) >> BdrShift::Vsens as u8;
+                if events && j >= fifo_out_slot.len() {
+                    *out_slot_size = j as u16;
+                    return Status::Err;
+                }
+
+                let bdr_acc_cfg = (raw_slot.fifo_data_out[6] & u8::from(BdrMask::Xl)) >> u8::from(BdrShift::Xl);
+                let bdr_gyr_cfg = (raw_slot.fifo_data_out[6] & u8::from(BdrMask::Gy)) >> u8::from(BdrShift::Gy);
+                let bdr_vsens_cfg = (raw_slot.fifo_data_out[4] & u8::from(BdrMask::Vsens)) >> u8::from(BdrShift::Vsens);
 
                 let bdr_xl = DEVICES[self.fifo_ver as usize].bdr_acc[bdr_acc_cfg as usize];
                 let bdr_gy = DEVICES[self.fifo_ver as usize].bdr_gyr[bdr_gyr_cfg as usize];
                 let bdr_vsens = DEVICES[self.fifo_ver as usize].bdr_vsens[bdr_vsens_cfg as usize];
-                let bdr_max = max(max(bdr_xl, bdr_gy), bdr_vsens);
+                let bdr_max = max(max(max(max(bdr_xl, bdr_gy), bdr_vsens), self.bdr_hg), self.bdr_aux);
 
                 self.dtime_xl_old = self.dtime_xl;
                 self.dtime_gy_old = self.dtime_gy;
@@ -134,12 +413,83 @@ impl FifoData {
 
                 self.bdr_chg_xl_flag = 1;
                 self.bdr_chg_gy_flag = 1;
+                self.gap_dtime_old = [self.dtime_xl_old, self.dtime_gy_old];
+                self.stats.odr_changes += 1;
+
+                // codes missing from the table of the sensor (e.g. the gyroscope at 1.6 Hz) are off
+                let bdr = |hz: f32, code: u8| if hz > 0.0 { Bdr::from_fifo_ver_code(self.fifo_ver, code).unwrap_or(Bdr::Off) } else { Bdr::Off };
+                self.bdr = BdrChangeData {
+                    xl: bdr(bdr_xl, bdr_acc_cfg),
+                    gy: bdr(bdr_gy, bdr_gyr_cfg),
+                    vsens: bdr(bdr_vsens, bdr_vsens_cfg),
+                };
+
+                if events {
+                    fifo_out_slot[j].timestamp = self.timestamp;
+                    fifo_out_slot[j].sensor_tag = SensorType::BdrChange;
+                    fifo_out_slot[j].sensor_data = self.bdr.into();
+                    j += 1;
+                    *out_slot_size = j as u16;
+                }
             } else if tag == Tag::Ts {
-                self.timestamp = u32::from_le_bytes(fifo_raw_slot[i].fifo_data_out[1..5].try_into().unwrap());
+                let timestamp = u32::from_le_bytes(raw_slot.fifo_data_out[1..5].try_into().unwrap());
+
+                if self.ts_synced && self.dtime_min != 0 && timestamp > self.timestamp {
+                    let lost_slots = (timestamp - self.timestamp).saturating_add(self.dtime_min / 2) / self.dtime_min;
+
+                    if lost_slots > 0 {
+                        if events && j >= fifo_out_slot.len() {
+                            *out_slot_size = j as u16;
+                            return Status::Err;
+                        }
+
+                        // the gap is reported once, not again by the next samples
+                        self.gap_last = [None; 2];
+                        j = self.push_data_loss(fifo_out_slot, j, self.timestamp, DataLossCause::Timestamp, SensorType::None, lost_slots, events);
+                        *out_slot_size = j as u16;
+                    }
+                }
+
+                let correction = timestamp.wrapping_sub(self.timestamp) as i32;
+
+                if !self.ts_synced {
+                    // the samples decoded so far were timed from an arbitrary origin
+                    self.shift_time_base(correction);
+                } else if correction.unsigned_abs() < self.dtime_min / 2 {
+                    self.ts_drift = correction.unsigned_abs();
+                }
+
+                self.timestamp = timestamp;
+                self.ts_last = timestamp;
+                self.ts_correction = correction;
+                self.ts_synced = true;
             } else {
                 let compression_type = Self::get_compression_type(&tag);
                 let sensor_type = Self::get_sensor_type(&tag);
 
+                if sensor_type == SensorType::Accelerometer || sensor_type == SensorType::Gyroscope {
+                    self.stats.compression[compression_type as usize] += 1;
+                }
+
+                let samples = match compression_type {
+                    CompressionType::Nc if tag == Tag::Empty => 0,
+                    CompressionType::Nc | CompressionType::NcT1 | CompressionType::NcT2 => 1,
+                    CompressionType::Comp2x => 2,
+                    CompressionType::Comp3x => 3,
+                };
+
+                // room for the DataLoss slot of a gap before the first sample, see check_gap
+                let loss_slots = match sensor_type {
+                    SensorType::Accelerometer if events => self.gap_last[0].is_some() as usize,
+                    SensorType::Gyroscope if events => self.gap_last[1].is_some() as usize,
+                    _ => 0,
+                };
+
+                if j + samples + loss_slots > fifo_out_slot.len() {
+                    *out_slot_size = j as u16;
+                    return Status::Err;
+                }
+
                 match compression_type {
                     CompressionType::Nc => {
                         if tag == Tag::Empty {
@@ -147,13 +497,15 @@ impl FifoData {
                         }
 
                         if tag == Tag::StepCounter || tag == Tag::MlcResult {
-                            fifo_out_slot[j].timestamp = u32::from_le_bytes(fifo_raw_slot[i].fifo_data_out[3..7].try_into().unwrap());
+                            fifo_out_slot[j].timestamp = u32::from_le_bytes(raw_slot.fifo_data_out[3..7].try_into().unwrap());
+                        } else if let Some(n) = Self::aux_channel(sensor_type) {
+                            fifo_out_slot[j].timestamp = self.aux_timestamp(n);
                         } else {
                             fifo_out_slot[j].timestamp = self.timestamp;
                         }
 
-                        fifo_out_slot[j].sensor_tag = sensor_type.clone();
-                        fifo_out_slot[j].sensor_data.from_u8_arr(&fifo_raw_slot[i].fifo_data_out[1..7]);
+                        fifo_out_slot[j].sensor_tag = sensor_type;
+                        fifo_out_slot[j].sensor_data.from_u8_arr(&raw_slot.fifo_data_out[1..7]);
 
                         if sensor_type == SensorType::Accelerometer {
                             self.last_data_xl = fifo_out_slot[j].sensor_data.data;
@@ -170,14 +522,14 @@ impl FifoData {
                         j += 1;
                     }
                     CompressionType::NcT1 => {
-                        fifo_out_slot[j].sensor_tag = sensor_type.clone();
-                        fifo_out_slot[j].sensor_data.from_u8_arr(&fifo_raw_slot[i].fifo_data_out[1..7]);
+                        fifo_out_slot[j].sensor_tag = sensor_type;
+                        fifo_out_slot[j].sensor_data.from_u8_arr(&raw_slot.fifo_data_out[1..7]);
 
                         if sensor_type == SensorType::Accelerometer {
                             let last_timestamp = if self.bdr_chg_xl_flag == 1 {
-                                self.last_timestamp_xl + self.dtime_xl_old
+                                self.last_timestamp_xl.wrapping_add(self.dtime_xl_old)
                             } else {
-                                self.timestamp - self.dtime_xl
+                                self.timestamp.wrapping_sub(self.dtime_xl)
                             };
 
                             fifo_out_slot[j].timestamp = last_timestamp;
@@ -187,9 +539,9 @@ impl FifoData {
 
                         if sensor_type == SensorType::Gyroscope {
                             let last_timestamp = if self.bdr_chg_gy_flag == 1 {
-                                self.last_timestamp_gy + self.dtime_gy_old
+                                self.last_timestamp_gy.wrapping_add(self.dtime_gy_old)
                             } else {
-                                self.timestamp - self.dtime_gy
+                                self.timestamp.wrapping_sub(self.dtime_gy)
                             };
 
                             fifo_out_slot[j].timestamp = last_timestamp;
@@ -200,14 +552,14 @@ impl FifoData {
                         j += 1;
                     }
                     CompressionType::NcT2 => {
-                        fifo_out_slot[j].sensor_tag = sensor_type.clone();
-                        fifo_out_slot[j].sensor_data.from_u8_arr(&fifo_raw_slot[i].fifo_data_out[1..7]);
+                        fifo_out_slot[j].sensor_tag = sensor_type;
+                        fifo_out_slot[j].sensor_data.from_u8_arr(&raw_slot.fifo_data_out[1..7]);
 
                         if sensor_type == SensorType::Accelerometer {
                             let last_timestamp = if self.bdr_chg_xl_flag == 1 {
-                                self.last_timestamp_xl + self.dtime_xl_old
+                                self.last_timestamp_xl.wrapping_add(self.dtime_xl_old)
                             } else {
-                                self.timestamp - 2 * self.dtime_xl
+                                self.timestamp.wrapping_sub(2 * self.dtime_xl)
                             };
 
                             fifo_out_slot[j].timestamp = last_timestamp;
@@ -217,9 +569,9 @@ impl FifoData {
 
                         if sensor_type == SensorType::Gyroscope {
                             let last_timestamp = if self.bdr_chg_gy_flag == 1 {
-                                self.last_timestamp_gy + self.dtime_gy_old
+                                self.last_timestamp_gy.wrapping_add(self.dtime_gy_old)
                             } else {
-                                self.timestamp - 2 * self.dtime_gy
+                                self.timestamp.wrapping_sub(2 * self.dtime_gy)
                             };
 
                             fifo_out_slot[j].timestamp = last_timestamp;
@@ -231,42 +583,42 @@ impl FifoData {
                     }
                     CompressionType::Comp2x => {
                         let mut diff = [0i16; 6];
-                        FifoData::get_diff_2x(&mut diff, &fifo_raw_slot[i].fifo_data_out[1..7]);
+                        FifoData::get_diff_2x(&mut diff, &raw_slot.fifo_data_out[1..7]);
 
-                        fifo_out_slot[j].sensor_tag = sensor_type.clone();
+                        fifo_out_slot[j].sensor_tag = sensor_type;
 
                         if sensor_type == SensorType::Accelerometer {
                             let data = [
-                                self.last_data_xl[0] + diff[0],
-                                self.last_data_xl[1] + diff[1],
-                                self.last_data_xl[2] + diff[2],
+                                self.last_data_xl[0].wrapping_add(diff[0]),
+                                self.last_data_xl[1].wrapping_add(diff[1]),
+                                self.last_data_xl[2].wrapping_add(diff[2]),
                             ];
-                            fifo_out_slot[j].timestamp = self.timestamp - 2 * self.dtime_xl;
+                            fifo_out_slot[j].timestamp = self.timestamp.wrapping_sub(2 * self.dtime_xl);
                             self.last_data_xl = data;
                             fifo_out_slot[j].sensor_data.data = data;
                         }
 
                         if sensor_type == SensorType::Gyroscope {
                             let data =[
-                                self.last_data_gy[0] + diff[0],
-                                self.last_data_gy[1] + diff[1],
-                                self.last_data_gy[2] + diff[2],
+                                self.last_data_gy[0].wrapping_add(diff[0]),
+                                self.last_data_gy[1].wrapping_add(diff[1]),
+                                self.last_data_gy[2].wrapping_add(diff[2]),
                             ];
-                            fifo_out_slot[j].timestamp = self.timestamp - 2 * self.dtime_gy;
+                            fifo_out_slot[j].timestamp = self.timestamp.wrapping_sub(2 * self.dtime_gy);
                             self.last_data_gy = data;
                             fifo_out_slot[j].sensor_data.data = data
                         }
 
                         j += 1;
 
-                        fifo_out_slot[j].sensor_tag = sensor_type.clone();
+                        fifo_out_slot[j].sensor_tag = sensor_type;
 
                         if sensor_type == SensorType::Accelerometer {
-                            let last_timestamp = self.timestamp - self.dtime_xl;
+                            let last_timestamp = self.timestamp.wrapping_sub(self.dtime_xl);
                             let data = [
-                                self.last_data_xl[0] + diff[3],
-                                self.last_data_xl[1] + diff[4],
-                                self.last_data_xl[2] + diff[5],
+                                self.last_data_xl[0].wrapping_add(diff[3]),
+                                self.last_data_xl[1].wrapping_add(diff[4]),
+                                self.last_data_xl[2].wrapping_add(diff[5]),
                             ];
                             fifo_out_slot[j].timestamp = last_timestamp;
                             self.last_data_xl = data;
@@ -275,11 +627,11 @@ impl FifoData {
                         }
 
                         if sensor_type == SensorType::Gyroscope {
-                            let last_timestamp = self.timestamp - self.dtime_gy;
+                            let last_timestamp = self.timestamp.wrapping_sub(self.dtime_gy);
                             let data = [
-                                self.last_data_gy[0] + diff[3],
-                                self.last_data_gy[1] + diff[4],
-                                self.last_data_gy[2] + diff[5],
+                                self.last_data_gy[0].wrapping_add(diff[3]),
+                                self.last_data_gy[1].wrapping_add(diff[4]),
+                                self.last_data_gy[2].wrapping_add(diff[5]),
                             ];
                             fifo_out_slot[j].timestamp = last_timestamp;
                             self.last_data_gy = data;
@@ -291,69 +643,70 @@ impl FifoData {
                     }
                     CompressionType::Comp3x => {
                         let mut diff = [0i16; 9];
-                        Self::get_diff_3x(&mut diff, &fifo_raw_slot[i].fifo_data_out[1..7]);
+                        Self::get_diff_3x(&mut diff, &raw_slot.fifo_data_out[1..7]);
 
-                        fifo_out_slot[j].sensor_tag = sensor_type.clone();
+                        fifo_out_slot[j].sensor_tag = sensor_type;
 
                         if sensor_type == SensorType::Accelerometer {
                             let data = [
-                                self.last_data_xl[0] + diff[0],
-                                self.last_data_xl[1] + diff[1],
-                                self.last_data_xl[2] + diff[2],
+                                self.last_data_xl[0].wrapping_add(diff[0]),
+                                self.last_data_xl[1].wrapping_add(diff[1]),
+                                self.last_data_xl[2].wrapping_add(diff[2]),
                             ];
-                            fifo_out_slot[j].timestamp = self.timestamp - 2 * self.dtime_xl;
+                            fifo_out_slot[j].timestamp = self.timestamp.wrapping_sub(2 * self.dtime_xl);
                             self.last_data_xl = data;
                             fifo_out_slot[j].sensor_data.data = data
                         }
 
                         if sensor_type == SensorType::Gyroscope {
                             let data = [
-                                self.last_data_gy[0] + diff[0],
-                                self.last_data_gy[1] + diff[1],
-                                self.last_data_gy[2] + diff[2],
+                                self.last_data_gy[0].wrapping_add(diff[0]),
+                                self.last_data_gy[1].wrapping_add(diff[1]),
+                                self.last_data_gy[2].wrapping_add(diff[2]),
                             ];
-                            fifo_out_slot[j].timestamp = self.timestamp - 2 * self.dtime_gy;
+                            fifo_out_slot[j].timestamp = self.timestamp.wrapping_sub(2 * self.dtime_gy);
                             self.last_data_gy = data;
                             fifo_out_slot[j].sensor_data.data = data;
                         }
 
                         j += 1;
 
+                        fifo_out_slot[j].sensor_tag = sensor_type;
 
                         if sensor_type == SensorType::Accelerometer {
                             let data = [
-                                self.last_data_xl[0] + diff[3],
-                                self.last_data_xl[1] + diff[4],
-                                self.last_data_xl[2] + diff[5],
+                                self.last_data_xl[0].wrapping_add(diff[3]),
+                                self.last_data_xl[1].wrapping_add(diff[4]),
+                                self.last_data_xl[2].wrapping_add(diff[5]),
                             ];
                             fifo_out_slot[j].sensor_data.data = data;
                             self.last_data_xl = data;
-                            fifo_out_slot[j].timestamp = self.timestamp - self.dtime_xl;
+                            fifo_out_slot[j].timestamp = self.timestamp.wrapping_sub(self.dtime_xl);
                             self.last_timestamp_xl = self.timestamp;
                         }
 
                         if sensor_type == SensorType::Gyroscope {
                             let data = [
-                                self.last_data_gy[0] + diff[3],
-                                self.last_data_gy[1] + diff[4],
-                                self.last_data_gy[2] + diff[5],
+                                self.last_data_gy[0].wrapping_add(diff[3]),
+                                self.last_data_gy[1].wrapping_add(diff[4]),
+                                self.last_data_gy[2].wrapping_add(diff[5]),
                             ];
                             fifo_out_slot[j].sensor_data.data = data; 
                             self.last_data_gy = data;
-                            fifo_out_slot[j].timestamp = self.timestamp - self.dtime_gy;
+                            fifo_out_slot[j].timestamp = self.timestamp.wrapping_sub(self.dtime_gy);
                             self.last_timestamp_gy = self.timestamp;
                         }
 
                         j += 1;
 
                         fifo_out_slot[j].timestamp = self.timestamp;
-                        fifo_out_slot[j].sensor_tag = sensor_type.clone();
+                        fifo_out_slot[j].sensor_tag = sensor_type;
 
                         if sensor_type == SensorType::Accelerometer {
                             let data = [
-                                self.last_data_xl[0] + diff[6],
-                                self.last_data_xl[1] + diff[7],
-                                self.last_data_xl[2] + diff[8],
+                                self.last_data_xl[0].wrapping_add(diff[6]),
+                                self.last_data_xl[1].wrapping_add(diff[7]),
+                                self.last_data_xl[2].wrapping_add(diff[8]),
                             ];
                             self.last_data_xl = data;
                             fifo_out_slot[j].sensor_data.data = data;
@@ -362,9 +715,9 @@ impl FifoData {
 
                         if sensor_type == SensorType::Gyroscope {
                             let data = [
-                                self.last_data_gy[0] + diff[6],
-                                self.last_data_gy[1] + diff[7],
-                                self.last_data_gy[2] + diff[8],
+                                self.last_data_gy[0].wrapping_add(diff[6]),
+                                self.last_data_gy[1].wrapping_add(diff[7]),
+                                self.last_data_gy[2].wrapping_add(diff[8]),
                             ];
                             self.last_data_gy = data;
                             fifo_out_slot[j].sensor_data.data = data;
@@ -379,10 +732,123 @@ impl FifoData {
             }
 
             self.tag_counter_old = tag_counter;
+
+            let mut gaps = [None; 3];
+
+            for (gap, slot) in gaps.iter_mut().zip(&fifo_out_slot[j_start..j]) {
+                self.stats.samples[slot.sensor_tag as usize] += 1;
+                *gap = self.check_gap(slot);
+            }
+
+            for (timestamp, sensor_type, lost_samples) in gaps.into_iter().flatten() {
+                j = self.push_data_loss(fifo_out_slot, j, timestamp, DataLossCause::TagCounter, sensor_type, lost_samples, events);
+                *out_slot_size = j as u16;
+            }
         }
 
         Status::Ok
     }
+
+    /// Returns the batch data rates in effect: the configured ones, or the ones
+    /// of the last ODRCHG word.
+    pub fn bdr(&self) -> BdrChangeData {
+        self.bdr
+    }
+
+    /// Returns the decoding statistics since `init` or the last `reset_stats`.
+    pub fn stats(&self) -> &FifoStats {
+        &self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = FifoStats::default();
+    }
+
+    fn aux_channel(sensor_type: SensorType) -> Option<usize> {
+        match sensor_type {
+            SensorType::ExtSensor0 => Some(0),
+            SensorType::ExtSensor1 => Some(1),
+            SensorType::ExtSensor2 => Some(2),
+            SensorType::ExtSensor3 => Some(3),
+            SensorType::DualAccel => Some(4),
+            SensorType::EisGyro => Some(5),
+            SensorType::Temperature => Some(6),
+            _ => None,
+        }
+    }
+
+    /// Snaps the timestamp of a sample of an auxiliary channel (sensor-hub slot,
+    /// dual-channel accelerometer, EIS gyroscope, temperature) to the period of the channel:
+    /// the word is written in the first time slot after the sample, which is on
+    /// the grid of the fastest sensor.
+    fn aux_timestamp(&mut self, n: usize) -> u32 {
+        let dtime = self.dtime_aux[n];
+
+        let timestamp = match self.last_timestamp_aux[n] {
+            Some(last) if dtime != 0 && self.timestamp > last => {
+                let periods = ((self.timestamp - last).saturating_add(dtime / 2) / dtime).max(1);
+                last.wrapping_add(periods.wrapping_mul(dtime))
+            }
+            _ => self.timestamp,
+        };
+
+        self.last_timestamp_aux[n] = Some(timestamp);
+        timestamp
+    }
+
+    /// Checks the accelerometer and gyroscope sample against the previous one and
+    /// returns the timestamp, sensor and number of the missing samples, if any.
+    fn check_gap(&mut self, slot: &OutSlot) -> Option<(u32, SensorType, u32)> {
+        let (k, dtime) = match slot.sensor_tag {
+            SensorType::Accelerometer => (0, self.dtime_xl),
+            SensorType::Gyroscope => (1, self.dtime_gy),
+            _ => return None,
+        };
+
+        // the first sample after an ODR change can come after the old period
+        let dtime = max(dtime, self.gap_dtime_old[k]);
+        let last = self.gap_last[k].replace(slot.timestamp);
+        self.gap_dtime_old[k] = 0;
+
+        match last {
+            Some(last) if dtime != 0 && slot.timestamp > last => {
+                let lost_samples = ((slot.timestamp - last).saturating_add(dtime / 2) / dtime).saturating_sub(1);
+                (lost_samples > 0).then_some((last.wrapping_add(dtime), slot.sensor_tag, lost_samples))
+            }
+            _ => None,
+        }
+    }
+
+    /// Updates the statistics and, if `events`, appends a `SensorType::DataLoss`
+    /// slot: the caller checks that `fifo_out_slot` has room for it.
+    #[allow(clippy::too_many_arguments)]
+    fn push_data_loss(
+        &mut self,
+        fifo_out_slot: &mut [OutSlot],
+        j: usize,
+        timestamp: u32,
+        cause: DataLossCause,
+        sensor_type: SensorType,
+        lost_samples: u32,
+        events: bool,
+    ) -> usize {
+        self.stats.gaps = self.stats.gaps.wrapping_add(1);
+        self.stats.lost_samples = self.stats.lost_samples.saturating_add(lost_samples);
+
+        if !events {
+            return j;
+        }
+
+        let slot = &mut fifo_out_slot[j];
+        slot.timestamp = timestamp;
+        slot.sensor_tag = SensorType::DataLoss;
+        slot.sensor_data = DataLossData { cause, sensor_type, lost_samples: lost_samples.min(u16::MAX as u32) as u16 }.into();
+
+        j + 1
+    }
+
+    /// Converts the 6 bytes of a FIFO payload into three little-endian words, as
+    /// `SensorData::from_u8_arr`.
     pub fn bytes_to_i16_array(source_bytes: &[u8; 6], destination: &mut [i16; 3]) {
         for (i, chunk) in source_bytes.chunks_exact(2).enumerate() {
             destination[i] = FifoData::combine_bytes_to_i16(chunk[0], chunk[1]);
@@ -390,7 +856,7 @@ impl FifoData {
     }
 
     pub fn combine_bytes_to_i16(low_byte: u8, high_byte: u8) -> i16 {
-        (((low_byte as u16) << 8) | high_byte as u16) as i16
+        i16::from_le_bytes([low_byte, high_byte])
     }
 
     
@@ -428,6 +894,7 @@ impl FifoData {
             Tag::MlcFeature => SensorType::MlcFeature,
             Tag::DualcXl => SensorType::DualAccel,
             Tag::EisGy => SensorType::EisGyro,
+            Tag::XlHg => SensorType::HighGAccel,
             _ => SensorType::None,
         }
     }
@@ -458,7 +925,7 @@ impl FifoData {
     }
 
     fn has_even_parity(x: u8) -> bool {
-        x.count_ones() % 2 == 0
+        x.count_ones() & 1 == 0
     }
 
     fn get_diff_2x(diff: &mut [i16; 6], input: &[u8]) {
@@ -479,25 +946,11 @@ impl FifoData {
     }
 
 
-    pub fn sort(&self, fifo_out_slot: &mut [OutSlot], out_slot_size: u16) {
-        for i in 1..out_slot_size as usize {
-            let temp = fifo_out_slot[i].clone();
-            let mut j: i32 = i as i32 - 1;
-
-            while j >= 0 && fifo_out_slot[j as usize].timestamp > temp.timestamp {
-                fifo_out_slot[(j + 1) as usize] = fifo_out_slot[j as usize].clone();
-                j -= 1;
-            }
-
-            fifo_out_slot[(j + 1) as usize] = temp;
-        }
-    }
-
     pub fn get_sensor_occurrence(&self, fifo_out_slot: &[OutSlot], out_slot_size: u16, sensor_type: SensorType) -> u16 {
         let mut occurrence = 0;
 
-        for i in 0..out_slot_size as usize {
-            if fifo_out_slot[i].sensor_tag == sensor_type {
+        for out_slot in fifo_out_slot.iter().take(out_slot_size as usize) {
+            if out_slot.sensor_tag == sensor_type {
                 occurrence += 1;
             }
         }
@@ -508,9 +961,9 @@ impl FifoData {
     pub fn extract_sensor(&self, sensor_out_slot: &mut [OutSlot], fifo_out_slot: &[OutSlot], out_slot_size: u16, sensor_type: SensorType) {
         let mut temp_i = 0;
 
-        for i in 0..out_slot_size as usize {
-            if fifo_out_slot[i].sensor_tag == sensor_type {
-                sensor_out_slot[temp_i] = fifo_out_slot[i].clone();
+        for out_slot in fifo_out_slot.iter().take(out_slot_size as usize) {
+            if out_slot.sensor_tag == sensor_type {
+                sensor_out_slot[temp_i] = *out_slot;
                 temp_i += 1;
             }
         }
@@ -520,7 +973,10 @@ impl FifoData {
 
 
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum CompressionType {
     Nc,
     NcT1,
@@ -533,6 +989,7 @@ pub struct Device {
     pub bdr_acc: [f32; 16],
     pub bdr_gyr: [f32; 16],
     pub bdr_vsens: [f32; 16],
+    pub bdr_hg: [f32; 16],
     pub dtime: [u32; 16],
     pub tag_valid_limit: u8,
 }
@@ -585,16 +1042,20 @@ pub enum TagShift {
     Sensor = 0x03,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Status {
     Ok = 0,
     Err = 1,
 }
 
 
-#[derive(PartialEq, Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Tag {
     Empty = 0x00,
     Gy = 0x01,
@@ -627,8 +1088,15 @@ pub enum Tag {
     MlcFeature = 0x1C,
     DualcXl = 0x1D,
     EisGy = 0x1E,
+    // Codes of the fifo_ver 2 parts that reuse the code of another tag: bit 5 set
+    // above the 5-bit code stored in the FIFO words, so `as u8` is not the stored
+    // code. Use Tag::from_raw and Tag::to_raw.
+    XlHg = TAG_ALIAS | 0x1D, // high-g accelerometer, DualcXl code
 }
 
+/// Bit of the `Tag` discriminants of the codes reused by the fifo_ver 2 parts.
+const TAG_ALIAS: u8 = 0x20;
+
 impl TryFrom<u8> for Tag {
     type Error = ();
 
@@ -670,8 +1138,25 @@ impl TryFrom<u8> for Tag {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Default)]
+impl Tag {
+    /// Decodes the tag code of a FIFO word of a `fifo_ver` device.
+    pub fn from_raw(value: u8, fifo_ver: u8) -> Result<Self, Status> {
+        match (value, fifo_ver) {
+            (0x1D, 2) => Ok(Tag::XlHg),
+            _ => Tag::try_from(value).map_err(|_| Status::Err),
+        }
+    }
+
+    /// Returns the tag code stored in the FIFO words.
+    pub const fn to_raw(self) -> u8 {
+        self as u8 & !TAG_ALIAS
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum SensorType {
     #[default] Gyroscope = 0,
     Accelerometer = 1,
@@ -694,6 +1179,51 @@ pub enum SensorType {
     DualAccel = 18,
     EisGyro = 19,
     None = 20,
+    HighGAccel = 21,
+    DataLoss = 22, // not a FIFO tag: reported by the decoder when samples are lost
+    BdrChange = 23, // not a FIFO tag: reported by the decoder when the batch data rates change
+    Custom0 = 24, // not a FIFO tag: entries 0 to 3 of Config::custom_tags, see TagContext
+    Custom1 = 25,
+    Custom2 = 26,
+    Custom3 = 27,
+}
+
+impl TryFrom<u8> for SensorType {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(SensorType::Gyroscope),
+            1 => Ok(SensorType::Accelerometer),
+            2 => Ok(SensorType::Temperature),
+            3 => Ok(SensorType::ExtSensor0),
+            4 => Ok(SensorType::ExtSensor1),
+            5 => Ok(SensorType::ExtSensor2),
+            6 => Ok(SensorType::ExtSensor3),
+            7 => Ok(SensorType::StepCounter),
+            8 => Ok(SensorType::GameRv6x),
+            9 => Ok(SensorType::GeomRv6x),
+            10 => Ok(SensorType::Rv9x),
+            11 => Ok(SensorType::GyroBias),
+            12 => Ok(SensorType::Gravity),
+            13 => Ok(SensorType::MagCalib),
+            14 => Ok(SensorType::ExtSensorNack),
+            15 => Ok(SensorType::MlcResult),
+            16 => Ok(SensorType::MlcFilter),
+            17 => Ok(SensorType::MlcFeature),
+            18 => Ok(SensorType::DualAccel),
+            19 => Ok(SensorType::EisGyro),
+            20 => Ok(SensorType::None),
+            21 => Ok(SensorType::HighGAccel),
+            22 => Ok(SensorType::DataLoss),
+            23 => Ok(SensorType::BdrChange),
+            24 => Ok(SensorType::Custom0),
+            25 => Ok(SensorType::Custom1),
+            26 => Ok(SensorType::Custom2),
+            27 => Ok(SensorType::Custom3),
+            _ => Err(()),
+        }
+    }
 }
 
 impl fmt::Display for SensorType {
@@ -720,25 +1250,51 @@ impl fmt::Display for SensorType {
             SensorType::DualAccel => "DualAccel",
             SensorType::EisGyro => "EisGyro",
             SensorType::None => "None",
+            SensorType::HighGAccel => "HighGAccel",
+            SensorType::DataLoss => "DataLoss",
+            SensorType::BdrChange => "BdrChange",
+            SensorType::Custom0 => "Custom0",
+            SensorType::Custom1 => "Custom1",
+            SensorType::Custom2 => "Custom2",
+            SensorType::Custom3 => "Custom3",
         };
         write!(f, "{}", name)
     }
 }
 
-#[derive(Default, Copy, Clone)]
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct RawSlot {
     pub fifo_data_out: [u8; 7], // registers from mems (78h -> 7Dh)
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OutSlot {
     pub timestamp: u32,
     pub sensor_tag: SensorType,
     pub sensor_data: SensorData,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+impl OutSlot {
+    /// Returns the timestamp in nanoseconds using the given time base.
+    pub fn timestamp_ns(&self, time_base: &TimeBase) -> u64 {
+        time_base.ticks_to_ns(self.timestamp)
+    }
+
+    /// Returns the timestamp as a `Duration` using the given time base.
+    pub fn timestamp_duration(&self, time_base: &TimeBase) -> Duration {
+        time_base.ticks_to_duration(self.timestamp)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 #[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DeviceType {
     Lsm6dsr = 0,
     Lsm6dsrx = 1,
@@ -752,11 +1308,147 @@ pub enum DeviceType {
     Lsm6dsv = 9,
     Lsm6dsv16x = 10,
     Lsm6dsv32x = 11,
+    Lsm6dsv80x = 12,
+    Lsm6dsv320x = 13,
+    Ism6hg256x = 14,
+    Lsm6dsl = 15,   // pattern-based FIFO, see `PatternFifo`
+    Ism330dlc = 16, // pattern-based FIFO, see `PatternFifo`
+}
+
+impl TryFrom<u8> for DeviceType {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(DeviceType::Lsm6dsr),
+            1 => Ok(DeviceType::Lsm6dsrx),
+            2 => Ok(DeviceType::Asm330lhh),
+            3 => Ok(DeviceType::Asm330lhhx),
+            4 => Ok(DeviceType::Ism330dhcx),
+            5 => Ok(DeviceType::Lsm6dso),
+            6 => Ok(DeviceType::Lsm6dsox),
+            7 => Ok(DeviceType::Lsm6dso32),
+            8 => Ok(DeviceType::Lsm6dso32x),
+            9 => Ok(DeviceType::Lsm6dsv),
+            10 => Ok(DeviceType::Lsm6dsv16x),
+            11 => Ok(DeviceType::Lsm6dsv32x),
+            12 => Ok(DeviceType::Lsm6dsv80x),
+            13 => Ok(DeviceType::Lsm6dsv320x),
+            14 => Ok(DeviceType::Ism6hg256x),
+            15 => Ok(DeviceType::Lsm6dsl),
+            16 => Ok(DeviceType::Ism330dlc),
+            _ => Err(()),
+        }
+    }
+}
+
+impl DeviceType {
+    /// Returns the FIFO generation of the device (index into `DEVICES`).
+    pub const fn fifo_ver(&self) -> u8 {
+        match self {
+            DeviceType::Lsm6dsr
+            | DeviceType::Lsm6dsrx
+            | DeviceType::Asm330lhh
+            | DeviceType::Asm330lhhx
+            | DeviceType::Ism330dhcx
+            | DeviceType::Lsm6dso
+            | DeviceType::Lsm6dsox
+            | DeviceType::Lsm6dso32
+            | DeviceType::Lsm6dso32x
+            | DeviceType::Lsm6dsl
+            | DeviceType::Ism330dlc => 0,
+            DeviceType::Lsm6dsv | DeviceType::Lsm6dsv16x | DeviceType::Lsm6dsv32x => 1,
+            DeviceType::Lsm6dsv80x | DeviceType::Lsm6dsv320x | DeviceType::Ism6hg256x => 2,
+        }
+    }
+
+    /// Returns true for the devices with a pattern-based FIFO (no tags), decoded
+    /// by `PatternFifo` instead of `FifoData`.
+    pub const fn is_pattern_fifo(&self) -> bool {
+        matches!(self, DeviceType::Lsm6dsl | DeviceType::Ism330dlc)
+    }
 }
 
+/// Timestamp batching decimation (TS word every 1, 8 or 32 time slots).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TsDecimation {
+    Off,
+    X1,
+    X8,
+    X32,
+}
+
+impl TsDecimation {
+    /// Returns the number of time slots between two TS words (0 if not batched).
+    pub const fn factor(&self) -> u32 {
+        match self {
+            TsDecimation::Off => 0,
+            TsDecimation::X1 => 1,
+            TsDecimation::X8 => 8,
+            TsDecimation::X32 => 32,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Config {
     pub device: DeviceType, // device to select
-    pub bdr_xl: f32,    // accelerometer batch data rate in Hz
-    pub bdr_gy: f32,    // gyroscope batch data rate in Hz
-    pub bdr_vsens: f32, // virtual sensor batch data rate in Hz
+    pub bdr_xl: Bdr,    // accelerometer batch data rate
+    pub bdr_gy: Bdr,    // gyroscope batch data rate
+    pub bdr_vsens: Bdr, // virtual sensor batch data rate
+    pub bdr_hg: Bdr,    // high-g accelerometer batch data rate
+    pub time_base: TimeBase, // timestamp LSB and FREQ_FINE trimming
+    pub fs_xl: XlFullScale, // accelerometer full scale
+    pub fs_gy: GyFullScale, // gyroscope full scale
+    pub fs_hg: Option<HgFullScale>, // high-g accelerometer full scale
+    pub bdr_dualc: Bdr, // dual-channel accelerometer batch data rate (LSM6DSV-class parts)
+    pub fs_dualc: XlFullScale, // dual-channel accelerometer full scale
+    pub bdr_eis: Bdr, // EIS gyroscope batch data rate (fifo_ver 1 and 2 parts)
+    pub fs_eis: GyFullScale, // EIS gyroscope full scale
+    pub ext_sensors: [Option<ExtSensorSlot>; 4], // sensor-hub slots (ExtSens0 to ExtSens3)
+    pub ts_decimation: TsDecimation, // timestamp batching decimation set in the sensor
+    pub bdr_temp: Bdr, // temperature batch data rate (1.6/12.5/52 Hz, or 1.875/15/60 Hz on the fifo_ver 1 and 2 parts)
+    pub pattern: PatternConfig, // data sets of the pattern-based FIFO (LSM6DSL, ISM330DLC)
+    pub custom_tags: TagRegistry, // tags unknown to the crate, see FifoData::decode_custom
+}
+
+impl Config {
+    /// Creates a configuration with every sensor off, the nominal time base and the
+    /// lowest full scales of the device.
+    pub const fn new(device: DeviceType) -> Self {
+        Config {
+            device,
+            bdr_xl: Bdr::Off,
+            bdr_gy: Bdr::Off,
+            bdr_vsens: Bdr::Off,
+            bdr_hg: Bdr::Off,
+            time_base: TimeBase::new(device),
+            fs_xl: device.units().xl_fs[0],
+            fs_gy: device.units().gy_fs[0],
+            fs_hg: match device.units().hg_fs {
+                [fs_hg, ..] => Some(*fs_hg),
+                [] => None,
+            },
+            bdr_dualc: Bdr::Off,
+            fs_dualc: device.units().xl_fs[0],
+            bdr_eis: Bdr::Off,
+            fs_eis: device.units().gy_fs[0],
+            ext_sensors: [None; 4],
+            ts_decimation: TsDecimation::Off,
+            bdr_temp: Bdr::Off,
+            pattern: PatternConfig::new(),
+            custom_tags: TagRegistry::new(),
+        }
+    }
+
+    /// Returns the highest batch data rate of the channels not reported by the
+    /// ODRCHG word: sensor-hub slots, dual-channel accelerometer, EIS gyroscope
+    /// and temperature.
+    pub(crate) fn bdr_aux(&self) -> f32 {
+        max(max(max(self.bdr_ext(), self.bdr_dualc.hz()), self.bdr_eis.hz()), self.bdr_temp.hz())
+    }
 }
@@ -0,0 +1,303 @@
+//! Decoder of the pattern-based FIFO of the LSM6DSL and ISM330DLC.
+//!
+//! These FIFOs have no tags: the samples are stored as 16-bit words in a
+//! recursive pattern of four data sets (gyroscope, accelerometer, sensor hub
+//! and DS4), each at the FIFO ODR divided by its decimation factor. Within a
+//! FIFO ODR period the data sets are stored in this order, and the FIFO_PATTERN
+//! register gives the position in the pattern of the next word to be read.
+
+use crate::{Bdr, Config, DEVICES, DataLossCause, DataLossData, FifoData, OutSlot, SensorType, Status};
+
+/// Decimation of a FIFO data set (DEC_FIFO_GYRO, DEC_FIFO_XL, DEC_DS3_FIFO and
+/// DEC_DS4_FIFO register codes).
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FifoDecimation {
+    #[default]
+    Off = 0, // data set not in FIFO
+    X1 = 1,
+    X2 = 2,
+    X3 = 3,
+    X4 = 4,
+    X8 = 5,
+    X16 = 6,
+    X32 = 7,
+}
+
+impl FifoDecimation {
+    /// Returns the number of FIFO ODR periods between two samples (0 if not batched).
+    pub const fn factor(&self) -> u32 {
+        match self {
+            FifoDecimation::Off => 0,
+            FifoDecimation::X1 => 1,
+            FifoDecimation::X2 => 2,
+            FifoDecimation::X3 => 3,
+            FifoDecimation::X4 => 4,
+            FifoDecimation::X8 => 8,
+            FifoDecimation::X16 => 16,
+            FifoDecimation::X32 => 32,
+        }
+    }
+}
+
+/// Content of the fourth data set.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Ds4Content {
+    #[default]
+    StepCounter, // step counter and timestamp (TIMER_PEDO_FIFO_EN)
+    Temperature, // temperature (FIFO_TEMP_EN)
+}
+
+/// Configuration of the pattern-based FIFO.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PatternConfig {
+    pub odr_fifo: Bdr,           // FIFO ODR (ODR_FIFO)
+    pub dec_gy: FifoDecimation,  // first data set: gyroscope
+    pub dec_xl: FifoDecimation,  // second data set: accelerometer
+    pub dec_ds3: FifoDecimation, // third data set: sensor hub, reported as ExtSensor0
+    pub dec_ds4: FifoDecimation, // fourth data set
+    pub ds4: Ds4Content,
+}
+
+impl PatternConfig {
+    /// Creates a configuration with the FIFO off.
+    pub const fn new() -> Self {
+        PatternConfig {
+            odr_fifo: Bdr::Off,
+            dec_gy: FifoDecimation::Off,
+            dec_xl: FifoDecimation::Off,
+            dec_ds3: FifoDecimation::Off,
+            dec_ds4: FifoDecimation::Off,
+            ds4: Ds4Content::StepCounter,
+        }
+    }
+}
+
+impl Default for PatternConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decoder of the pattern-based FIFO.
+///
+/// The samples are timed in timestamp ticks (25 µs, see `TimeBase`) from the
+/// first decoded pattern; the step counter samples carry their own timestamp.
+#[derive(Clone)]
+pub struct PatternFifo {
+    datasets: [(SensorType, u32); 4], // sensor and decimation factor of each data set
+    ticks: u32,                       // pattern length in FIFO ODR periods
+    words: u16,                       // pattern length in words
+    dtime: u32,                       // FIFO ODR period in timestamp ticks
+    started: bool,
+    position: u16,     // position in the pattern of the next word
+    tick: u32,         // FIFO ODR period of the current data set
+    dataset: usize,    // current data set
+    axis: usize,       // word of the current sample
+    partial: bool,     // the first words of the current sample were not read
+    pattern_time: u32, // timestamp of the current pattern
+    sample: [u16; 3],
+}
+
+impl PatternFifo {
+    pub fn init(conf: &Config) -> Result<Self, Status> {
+        let pattern = &conf.pattern;
+
+        if !conf.device.is_pattern_fifo() || pattern.odr_fifo.is_off() {
+            return Err(Status::Err);
+        }
+
+        let table = &DEVICES[0].bdr_acc;
+        pattern.odr_fifo.index(conf.device, table)?;
+
+        let ds4 = match pattern.ds4 {
+            Ds4Content::StepCounter => SensorType::StepCounter,
+            Ds4Content::Temperature => SensorType::Temperature,
+        };
+        let datasets = [
+            (SensorType::Gyroscope, pattern.dec_gy.factor()),
+            (SensorType::Accelerometer, pattern.dec_xl.factor()),
+            (SensorType::ExtSensor0, pattern.dec_ds3.factor()),
+            (ds4, pattern.dec_ds4.factor()),
+        ];
+
+        // the pattern repeats after the least common multiple of the decimation factors
+        let ticks = datasets.iter().map(|(_, dec)| *dec).filter(|dec| *dec != 0).fold(1, lcm);
+        let words: u32 = datasets.iter().filter(|(_, dec)| *dec != 0).map(|(_, dec)| 3 * ticks / dec).sum();
+
+        if words == 0 {
+            return Err(Status::Err);
+        }
+
+        Ok(PatternFifo {
+            datasets,
+            ticks,
+            words: words as u16,
+            dtime: DEVICES[0].dtime[FifoData::bdr_get_index(table, pattern.odr_fifo.hz())],
+            started: false,
+            position: 0,
+            tick: 0,
+            dataset: 0,
+            axis: 0,
+            partial: false,
+            pattern_time: 0,
+            sample: [0; 3],
+        })
+    }
+
+    /// Returns the number of words of the pattern.
+    pub fn pattern_words(&self) -> u16 {
+        self.words
+    }
+
+    /// Decodes `stream_size` words read from FIFO_DATA_OUT_L/H, given the
+    /// FIFO_PATTERN register read before the first of them.
+    ///
+    /// A sample split across two reads is output by the second one. When
+    /// FIFO_PATTERN is not the position following the previous read, words were
+    /// lost (FIFO overrun): a `SensorType::DataLoss` slot is emitted and the
+    /// decoder restarts from the new position, at least one pattern later if
+    /// the position is not ahead of the previous one.
+    pub fn decode(
+        &mut self,
+        fifo_out_slot: &mut [OutSlot],
+        fifo_data_out: &[u16],
+        out_slot_size: &mut u16,
+        stream_size: u16,
+        fifo_pattern: u16,
+    ) -> Status {
+        let mut j = 0;
+
+        if fifo_pattern >= self.words {
+            return Status::Err;
+        }
+
+        if !self.started || fifo_pattern != self.position {
+            if self.started {
+                if fifo_out_slot.is_empty() {
+                    return Status::Err;
+                }
+
+                fifo_out_slot[j].timestamp = self.sample_time();
+                fifo_out_slot[j].sensor_tag = SensorType::DataLoss;
+                fifo_out_slot[j].sensor_data =
+                    DataLossData { cause: DataLossCause::Overrun, sensor_type: SensorType::None, lost_samples: 0 }.into();
+                j += 1;
+
+                if fifo_pattern <= self.position {
+                    self.pattern_time = self.pattern_time.wrapping_add(self.ticks * self.dtime);
+                }
+            }
+
+            self.seek(fifo_pattern);
+            self.started = true;
+        }
+
+        for &word in fifo_data_out.iter().take(stream_size as usize) {
+            if self.axis == 2 && !self.partial && j == fifo_out_slot.len() {
+                *out_slot_size = j as u16;
+                return Status::Err;
+            }
+
+            self.sample[self.axis] = word;
+            self.axis += 1;
+
+            if self.axis == 3 {
+                if !self.partial {
+                    fifo_out_slot[j] = self.output();
+                    j += 1;
+                }
+
+                self.axis = 0;
+                self.partial = false;
+                self.next_dataset();
+            }
+
+            self.position = (self.position + 1) % self.words;
+        }
+
+        *out_slot_size = j as u16;
+
+        Status::Ok
+    }
+
+    fn output(&self) -> OutSlot {
+        let sensor_tag = self.datasets[self.dataset].0;
+        let mut slot = OutSlot { timestamp: self.sample_time(), sensor_tag, ..OutSlot::default() };
+
+        match sensor_tag {
+            SensorType::StepCounter => {
+                // TIMESTAMP[15:8], TIMESTAMP[23:16], -, TIMESTAMP[7:0], STEPS[7:0], STEPS[15:8]
+                let [t1, t2, _, t0, s0, s1] = self.bytes();
+                slot.timestamp = u32::from_le_bytes([t0, t1, t2, 0]);
+                slot.sensor_data.from_u8_arr(&[s0, s1, t0, t1, t2, 0]);
+            }
+            SensorType::Temperature => slot.sensor_data.data[0] = self.sample[0] as i16,
+            _ => slot.sensor_data.from_u8_arr(&self.bytes()),
+        }
+
+        slot
+    }
+
+    fn bytes(&self) -> [u8; 6] {
+        let [[b0, b1], [b2, b3], [b4, b5]] = self.sample.map(u16::to_le_bytes);
+        [b0, b1, b2, b3, b4, b5]
+    }
+
+    fn sample_time(&self) -> u32 {
+        self.pattern_time.wrapping_add(self.tick * self.dtime)
+    }
+
+    /// Moves to the word `position` of the current pattern.
+    fn seek(&mut self, position: u16) {
+        self.tick = 0;
+        self.dataset = self.datasets.iter().position(|(_, dec)| *dec != 0).unwrap_or(0);
+
+        for _ in 0..position / 3 {
+            self.next_dataset();
+        }
+
+        self.position = position;
+        self.axis = (position % 3) as usize;
+        self.partial = self.axis != 0;
+    }
+
+    /// Moves to the next data set stored in the pattern.
+    fn next_dataset(&mut self) {
+        loop {
+            self.dataset += 1;
+
+            if self.dataset == self.datasets.len() {
+                self.dataset = 0;
+                self.tick += 1;
+
+                if self.tick == self.ticks {
+                    self.tick = 0;
+                    self.pattern_time = self.pattern_time.wrapping_add(self.ticks * self.dtime);
+                }
+            }
+
+            let dec = self.datasets[self.dataset].1;
+
+            if dec != 0 && self.tick.is_multiple_of(dec) {
+                break;
+            }
+        }
+    }
+}
+
+fn lcm(a: u32, b: u32) -> u32 {
+    let (mut x, mut y) = (a, b);
+
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+
+    a / x * b
+}
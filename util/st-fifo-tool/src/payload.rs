@@ -0,0 +1,102 @@
+use crate::{AxisData, BdrChangeData, CompressionType, DataLossData, F16, FifoData, OutSlot, SensorData, SensorType, Tag};
+
+/// FIFO word payload decoded according to its tag.
+///
+/// All the fields are stored little-endian in the 6 bytes following the tag.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Payload {
+    Axis(AxisData),
+    Temperature(i16),
+    StepCounter { steps: u16, timestamp: u32 },
+    Quaternion { qx: F16, qy: F16, qz: F16 },
+    MlcResult { index: u8, value: u8, timestamp: u32 },
+    MlcFilter { id: u16, value: F16 },
+    MlcFeature { id: u16, value: F16 },
+    ExtSensor([u8; 6]),
+    ExtSensorNack { slave: u8 },
+    DataLoss(DataLossData),
+    BdrChange(BdrChangeData),
+    Raw([u8; 6]), // compressed words and tags without a known layout
+}
+
+impl Payload {
+    /// Decodes the payload of a raw FIFO word.
+    ///
+    /// Compressed words carry differences from the previous sample and can only be
+    /// interpreted by `FifoData::decode`: they are returned as `Payload::Raw`.
+    pub fn from_tag(tag: &Tag, raw: &[u8; 6]) -> Self {
+        match FifoData::get_compression_type(tag) {
+            CompressionType::Nc => Self::from_sensor_type(FifoData::get_sensor_type(tag), raw),
+            _ => Payload::Raw(*raw),
+        }
+    }
+
+    /// Decodes the payload of a (decompressed) sample of the given sensor.
+    pub fn from_sensor_type(sensor_type: SensorType, raw: &[u8; 6]) -> Self {
+        let u16_at = |i: usize| u16::from_le_bytes([raw[i], raw[i + 1]]);
+        let i16_at = |i: usize| i16::from_le_bytes([raw[i], raw[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes([raw[i], raw[i + 1], raw[i + 2], raw[i + 3]]);
+
+        match sensor_type {
+            SensorType::Accelerometer
+            | SensorType::Gyroscope
+            | SensorType::HighGAccel
+            | SensorType::DualAccel
+            | SensorType::EisGyro
+            | SensorType::GyroBias
+            | SensorType::Gravity
+            | SensorType::MagCalib => Payload::Axis(AxisData {
+                x: i16_at(0),
+                y: i16_at(2),
+                z: i16_at(4),
+            }),
+            SensorType::Temperature => Payload::Temperature(i16_at(0)),
+            SensorType::StepCounter => Payload::StepCounter {
+                steps: u16_at(0),
+                timestamp: u32_at(2),
+            },
+            SensorType::GameRv6x | SensorType::GeomRv6x | SensorType::Rv9x => Payload::Quaternion {
+                qx: F16(u16_at(0)),
+                qy: F16(u16_at(2)),
+                qz: F16(u16_at(4)),
+            },
+            SensorType::MlcResult => Payload::MlcResult {
+                value: raw[0],
+                index: raw[1],
+                timestamp: u32_at(2),
+            },
+            SensorType::MlcFilter => Payload::MlcFilter {
+                value: F16(u16_at(0)),
+                id: u16_at(2),
+            },
+            SensorType::MlcFeature => Payload::MlcFeature {
+                value: F16(u16_at(0)),
+                id: u16_at(2),
+            },
+            SensorType::ExtSensor0 | SensorType::ExtSensor1 | SensorType::ExtSensor2 | SensorType::ExtSensor3 => {
+                Payload::ExtSensor(*raw)
+            }
+            SensorType::ExtSensorNack => Payload::ExtSensorNack { slave: raw[0] },
+            SensorType::DataLoss => {
+                let data = [i16_at(0), i16_at(2), i16_at(4)];
+                Payload::DataLoss(SensorData { data }.to_data_loss())
+            }
+            SensorType::BdrChange => {
+                let data = [i16_at(0), i16_at(2), i16_at(4)];
+                Payload::BdrChange(SensorData { data }.to_bdr_change())
+            }
+            SensorType::None | SensorType::Custom0 | SensorType::Custom1 | SensorType::Custom2 | SensorType::Custom3 => {
+                Payload::Raw(*raw)
+            }
+        }
+    }
+}
+
+impl OutSlot {
+    /// Returns the typed payload of the decoded sample.
+    pub fn payload(&self) -> Payload {
+        Payload::from_sensor_type(self.sensor_tag, &self.sensor_data.to_bytes())
+    }
+}
@@ -0,0 +1,123 @@
+//! FIFO watermark and sizing planner.
+//!
+//! Estimates the FIFO fill rate for a batch data rate mix and derives the
+//! watermark that keeps the latency within a budget, and how long the host has
+//! to read the FIFO before it overruns.
+
+use crate::{Bdr, Config, DEVICES, Status, max};
+
+/// Samples packed in a 3x compressed word.
+const MAX_SAMPLES_PER_WORD: f32 = 3.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Compression {
+    Off,
+    /// Accelerometer and gyroscope compression enabled; `expected_ratio` is the
+    /// expected number of samples per word, from 1.0 to 3.0 (e.g. as measured by
+    /// `FifoStats::compression_ratio`).
+    On { expected_ratio: f32 },
+}
+
+/// FIFO settings to plan for.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Settings {
+    pub compression: Compression,
+    pub fifo_depth: u16,   // FIFO size in words
+    pub vsens_outputs: u8, // virtual sensor outputs batched at bdr_vsens (SFLP, ...)
+    pub latency: f32,      // maximum time in s a sample waits in the FIFO before the watermark
+}
+
+/// FIFO load of a configuration, see `plan`.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Plan {
+    pub samples_per_second: f32,
+    pub words_per_second: f32,     // expected, TS words included
+    pub max_words_per_second: f32, // worst case: no sample compressed
+    pub min_words_per_second: f32, // best case: every accelerometer/gyroscope word 3x compressed
+    pub worst_case_words_per_sample: f32,
+    pub watermark: u16,            // recommended watermark for the latency budget
+    pub time_to_overrun: f32,      // s to fill the empty FIFO, worst case
+    pub read_deadline: f32,        // s between the watermark and the overrun, worst case
+}
+
+/// Computes the FIFO load of the batch data rates and the timestamp batching
+/// decimation in `conf` with the given settings.
+///
+/// The batch data rates must be supported by the device (see `Bdr`).
+pub fn plan(conf: &Config, settings: &Settings) -> Result<Plan, Status> {
+    let device = &DEVICES[conf.device.fifo_ver() as usize];
+    let valid = |table: &[f32; 16], bdr: Bdr| bdr.index(conf.device, table).is_ok();
+
+    if conf.device.is_pattern_fifo()
+        || !valid(&device.bdr_acc, conf.bdr_xl)
+        || !valid(&device.bdr_gyr, conf.bdr_gy)
+        || !valid(&device.bdr_vsens, conf.bdr_vsens)
+        || !valid(&device.bdr_hg, conf.bdr_hg)
+        || !valid(&device.bdr_acc, conf.bdr_dualc)
+        || !valid(&device.bdr_gyr, conf.bdr_eis)
+        || !valid(&device.bdr_acc, conf.bdr_temp)
+        || conf.ext_sensors.iter().flatten().any(|slot| !valid(&device.bdr_acc, slot.bdr))
+        || settings.fifo_depth == 0
+        || settings.latency <= 0.0
+    {
+        return Err(Status::Err);
+    }
+
+    let ratio = match settings.compression {
+        Compression::Off => 1.0,
+        Compression::On { expected_ratio } if (1.0..=MAX_SAMPLES_PER_WORD).contains(&expected_ratio) => expected_ratio,
+        Compression::On { .. } => return Err(Status::Err),
+    };
+    let best_ratio = match settings.compression {
+        Compression::Off => 1.0,
+        Compression::On { .. } => MAX_SAMPLES_PER_WORD,
+    };
+
+    // one word per sample, except the accelerometer and gyroscope when compressed
+    let (bdr_xl, bdr_gy, bdr_vsens, bdr_hg) = (conf.bdr_xl.hz(), conf.bdr_gy.hz(), conf.bdr_vsens.hz(), conf.bdr_hg.hz());
+    let compressible = bdr_xl + bdr_gy;
+    let others = bdr_vsens * settings.vsens_outputs as f32
+        + bdr_hg
+        + conf.bdr_dualc.hz()
+        + conf.bdr_eis.hz()
+        + conf.bdr_temp.hz()
+        + conf.ext_sensors.iter().flatten().map(|slot| slot.bdr.hz()).sum::<f32>();
+
+    // a TS word every `decimation` time slots, at the rate of the fastest sensor
+    let slot_rate = max(max(max(bdr_xl, bdr_gy), max(bdr_vsens, bdr_hg)), conf.bdr_aux());
+    let ts = match conf.ts_decimation.factor() {
+        0 => 0.0,
+        decimation => slot_rate / decimation as f32,
+    };
+
+    let samples_per_second = compressible + others;
+
+    if samples_per_second == 0.0 {
+        return Err(Status::Err);
+    }
+
+    let max_words_per_second = compressible + others + ts;
+    let min_words_per_second = compressible / best_ratio + others + ts;
+    let depth = settings.fifo_depth as f32;
+
+    // the FIFO fills slowest when every sample is compressed: the watermark is
+    // reached within the latency budget even then
+    let watermark = (settings.latency * min_words_per_second).clamp(1.0, depth) as u16;
+
+    Ok(Plan {
+        samples_per_second,
+        words_per_second: compressible / ratio + others + ts,
+        max_words_per_second,
+        min_words_per_second,
+        worst_case_words_per_sample: max_words_per_second / samples_per_second,
+        watermark,
+        time_to_overrun: depth / max_words_per_second,
+        read_deadline: (depth - watermark as f32) / max_words_per_second,
+    })
+}
@@ -0,0 +1,172 @@
+//! Timestamp reconstruction with decimated or disabled timestamp batching.
+//!
+//! Between two TS words the decoder times the samples with the tag counter and
+//! the nominal time slot. `FifoData::decode_reconstructed` refines this:
+//! - the samples decoded before the first TS word (or after an overrun) are
+//!   moved to the time base of the TS word (back-fill);
+//! - the drift measured by a TS word, i.e. the difference with the timestamp
+//!   predicted by the tag counter, is spread linearly over the samples since the
+//!   previous TS word (interpolation);
+//! - a host clock reading taken when the FIFO is read anchors the sensor
+//!   timestamps to the host clock, also when no TS word is batched.
+
+use core::slice;
+
+use crate::{FifoData, OutSlot, RawSlot, SensorType, Status, Tag, TagMask, TagShift};
+
+/// Host clock readings matched to the sensor timestamps.
+#[derive(Clone, Copy, Default, Debug)]
+pub(crate) struct HostAnchors {
+    pub(crate) last: [Option<(u32, u64)>; 2], // (sensor timestamp, host time in ns), oldest first
+    pub(crate) residual_ns: Option<u64>,      // last reading against the time predicted by the previous ones
+}
+
+impl FifoData {
+    /// Decodes the FIFO words like `decode_with_overrun` and reconstructs the
+    /// timestamps of the samples of this read (see the module documentation).
+    ///
+    /// `host_ns` is the host clock, in nanoseconds, at the time of the last word
+    /// of `fifo_raw_slot`, e.g. read in the watermark interrupt when exactly the
+    /// watermark words are read. The samples are timed on the sensor time base:
+    /// `host_time_ns` converts them to the host clock.
+    pub fn decode_reconstructed(
+        &mut self,
+        fifo_out_slot: &mut [OutSlot],
+        fifo_raw_slot: &[RawSlot],
+        out_slot_size: &mut u16,
+        stream_size: u16,
+        fifo_ovr: bool,
+        host_ns: Option<u64>,
+    ) -> Status {
+        let mut j = 0;
+
+        if fifo_ovr {
+            let mut n = 0;
+
+            if self.decode_with_overrun(fifo_out_slot, &[], &mut n, 0, true) == Status::Err {
+                *out_slot_size = 0;
+                return Status::Err;
+            }
+
+            j = n as usize;
+        }
+
+        // first sample timed since the last TS word
+        let mut segment = j;
+
+        for raw_slot in fifo_raw_slot.iter().take(stream_size as usize) {
+            let ts_synced = self.ts_synced;
+            let ts_last = self.ts_last;
+            let mut n = 0;
+
+            if self.decode_with_overrun(&mut fifo_out_slot[j..], slice::from_ref(raw_slot), &mut n, 1, false) == Status::Err {
+                *out_slot_size = j as u16;
+                return Status::Err;
+            }
+
+            let tag = (raw_slot.fifo_data_out[0] & TagMask::Sensor as u8) >> TagShift::Sensor as u8;
+
+            if matches!(Tag::from_raw(tag, self.fifo_ver), Ok(Tag::Ts)) {
+                let correction = self.ts_correction;
+                let samples = fifo_out_slot[segment..j].iter_mut().filter(|slot| Self::has_slot_time(slot.sensor_tag));
+
+                if !ts_synced {
+                    for slot in samples {
+                        slot.timestamp = slot.timestamp.wrapping_add(correction as u32);
+                    }
+                } else if correction.unsigned_abs() < self.dtime_min / 2 {
+                    let span = self.ts_last.wrapping_sub(correction as u32).wrapping_sub(ts_last) as i64;
+
+                    for slot in samples {
+                        let elapsed = slot.timestamp.wrapping_sub(ts_last) as i64;
+
+                        if span > 0 && elapsed <= span {
+                            slot.timestamp = slot.timestamp.wrapping_add((correction as i64 * elapsed / span) as u32);
+                        }
+                    }
+                }
+
+                segment = j + n as usize;
+            }
+
+            j += n as usize;
+        }
+
+        *out_slot_size = j as u16;
+
+        if let Some(host_ns) = host_ns {
+            self.push_anchor(self.timestamp, host_ns);
+        }
+
+        Status::Ok
+    }
+
+    /// Converts a sensor timestamp to the host clock, in nanoseconds.
+    ///
+    /// The rate of the sensor clock is measured between the last two host
+    /// readings (the trimmed tick of `Config::time_base` is used until then).
+    /// Returns `None` before the first reading.
+    pub fn host_time_ns(&self, timestamp: u32) -> Option<u64> {
+        let (t1, h1) = self.anchors.last[1]?;
+
+        let ns_per_tick = match self.anchors.last[0] {
+            Some((t0, h0)) if t1 != t0 => (h1 as f64 - h0 as f64) / t1.wrapping_sub(t0) as f64,
+            _ => self.time_base.tick_ps() as f64 / 1000.0,
+        };
+
+        let elapsed = timestamp.wrapping_sub(t1) as i32 as f64;
+        let host = h1 as f64 + elapsed * ns_per_tick + 0.5;
+
+        Some(if host > 0.0 { host as u64 } else { 0 })
+    }
+
+    /// Returns the bound, in ticks, of the timestamp error of the decoded
+    /// samples on the sensor time base, or `None` before the first TS word.
+    ///
+    /// The samples are timed with the nominal time slot between two TS words: the
+    /// bound is the drift measured by the last TS word, over the number of TS
+    /// periods (`Config::ts_decimation`) elapsed since then.
+    pub fn ts_error_bound(&self) -> Option<u32> {
+        if !self.ts_synced {
+            return None;
+        }
+
+        let period = self.ts_decimation.factor() * self.dtime_min;
+        let elapsed = self.timestamp.wrapping_sub(self.ts_last);
+        let periods = if period == 0 { 1 } else { elapsed.div_ceil(period).max(1) };
+
+        Some(self.ts_drift.saturating_mul(periods))
+    }
+
+    /// Returns the error, in nanoseconds, of the last host reading against the
+    /// time predicted by the previous ones, or `None` before the second reading.
+    ///
+    /// It bounds the error of `host_time_ns` when the interrupt latency is steady.
+    pub fn host_error_bound_ns(&self) -> Option<u64> {
+        self.anchors.residual_ns
+    }
+
+    /// Moves the timing state to a new time base, `correction` ticks later.
+    pub(crate) fn shift_time_base(&mut self, correction: i32) {
+        let shift = |timestamp: u32| timestamp.wrapping_add(correction as u32);
+
+        self.last_timestamp_xl = shift(self.last_timestamp_xl);
+        self.last_timestamp_gy = shift(self.last_timestamp_gy);
+        self.last_timestamp_aux = self.last_timestamp_aux.map(|last| last.map(shift));
+        self.gap_last = self.gap_last.map(|last| last.map(shift));
+        self.anchors.last = self.anchors.last.map(|anchor| anchor.map(|(t, h)| (shift(t), h)));
+    }
+
+    fn push_anchor(&mut self, timestamp: u32, host_ns: u64) {
+        if let Some(predicted) = self.host_time_ns(timestamp) {
+            self.anchors.residual_ns = Some(host_ns.abs_diff(predicted));
+        }
+
+        self.anchors.last = [self.anchors.last[1], Some((timestamp, host_ns))];
+    }
+
+    /// Returns false for the samples carrying their own timestamp.
+    fn has_slot_time(sensor_type: SensorType) -> bool {
+        !matches!(sensor_type, SensorType::StepCounter | SensorType::MlcResult)
+    }
+}
@@ -1,13 +1,30 @@
-#[derive(Clone, Copy, Default)]
+use crate::{Bdr, DeviceType, SensorType};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SensorData {
     pub data: [i16; 3],
 }
 
+#[allow(clippy::wrong_self_convention)]
 impl SensorData {
+    /// Loads the 6 bytes of FIFO payload (little-endian words).
     pub fn from_u8_arr(&mut self, data: &[u8]) {
         for i in 0..3 {
-            self.data[i] = (data[i * 2] as i16) << 8 | data[i * 2 + 1] as i16;
+            self.data[i] = i16::from_le_bytes([data[i * 2], data[i * 2 + 1]]);
+        }
+    }
+
+    /// Returns the 6 bytes of FIFO payload.
+    pub fn to_bytes(&self) -> [u8; 6] {
+        let mut bytes = [0; 6];
+
+        for i in 0..3 {
+            bytes[i * 2..i * 2 + 2].copy_from_slice(&self.data[i].to_le_bytes());
         }
+
+        bytes
     }
 
     pub fn to_axis(&self) -> AxisData {
@@ -52,7 +69,7 @@ impl SensorData {
 
     pub fn to_mlc_result(&self) -> MlcResultData {
         MlcResultData {
-            mlc_res: (self.data[0] * 0xFF) as u8,
+            mlc_res: (self.data[0] & 0xFF) as u8,
             mlc_idx: (self.data[0] >> 8) as u8,
             mlc_t: [
                 (self.data[1] & 0xFF) as u8,
@@ -63,6 +80,31 @@ impl SensorData {
         }
     }
 
+    /// Converts a `SensorType::DataLoss` slot.
+    pub fn to_data_loss(&self) -> DataLossData {
+        DataLossData {
+            cause: match self.data[2] {
+                0 => DataLossCause::Overrun,
+                1 => DataLossCause::TagCounter,
+                _ => DataLossCause::Timestamp,
+            },
+            sensor_type: SensorType::try_from(self.data[1] as u8).unwrap_or(SensorType::None),
+            lost_samples: self.data[0] as u16,
+        }
+    }
+
+    /// Converts a `SensorType::BdrChange` slot.
+    pub fn to_bdr_change(&self) -> BdrChangeData {
+        // BDR register code, with bit 8 set for the BdrGen2 rates (fifo_ver 1 and 2)
+        let bdr = |value: i16| Bdr::from_fifo_ver_code((value >> 8 & 1) as u8, value as u8).unwrap_or(Bdr::Off);
+
+        BdrChangeData {
+            xl: bdr(self.data[0]),
+            gy: bdr(self.data[1]),
+            vsens: bdr(self.data[2]),
+        }
+    }
+
     pub fn to_mlc_filter_feature(&self) -> MlcFilterFeatureData {
         MlcFilterFeatureData {
             mlc_value: self.data[0] as u16,
@@ -73,47 +115,124 @@ impl SensorData {
 }
 
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AxisData {
     pub x: i16,
     pub y: i16,
     pub z: i16,
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct TemperatureData {
     pub temp: i16,
 }
 
-#[derive(Clone, Copy, Default)]
+impl TemperatureData {
+    /// Converts the sample into °C with the sensitivity and offset of `device`.
+    pub fn celsius(&self, device: DeviceType) -> f32 {
+        let units = device.units();
+        self.temp as f32 / units.temp_sensitivity + units.temp_offset
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct StepCounterData {
     pub steps: u16,
     pub steps_t: [u8; 4],
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct QuaternionData {
     pub qx: u16,
     pub qy: u16,
     pub qz: u16,
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ExtSensorNackData {
     pub nack: u8,
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct MlcResultData {
     pub mlc_res: u8,
     pub mlc_idx: u8,
     pub mlc_t: [u8; 4],
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct MlcFilterFeatureData {
     pub mlc_value: u16,
     pub mlc_id: u16,
     pub reserved: u16,
 }
 
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataLossCause {
+    Overrun = 0,    // FIFO_OVR flag set by the caller
+    TagCounter = 1, // sample later than its period
+    Timestamp = 2,  // TS word later than the predicted timestamp
+}
+
+/// Data loss reported by the decoder.
+///
+/// `lost_samples` counts the missing samples of `sensor_type` for
+/// `DataLossCause::TagCounter` and the missing time slots for
+/// `DataLossCause::Timestamp` (`sensor_type` is `SensorType::None`). It is 0
+/// for `DataLossCause::Overrun`, as the sensor does not report it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DataLossData {
+    pub cause: DataLossCause,
+    pub sensor_type: SensorType,
+    pub lost_samples: u16,
+}
+
+impl From<DataLossData> for SensorData {
+    fn from(value: DataLossData) -> Self {
+        SensorData {
+            data: [value.lost_samples as i16, value.sensor_type as i16, value.cause as i16],
+        }
+    }
+}
+
+/// Batch data rates in effect, reported by the decoder with a
+/// `SensorType::BdrChange` slot when an ODRCHG word changes them, and returned
+/// by `FifoData::bdr`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BdrChangeData {
+    pub xl: Bdr,
+    pub gy: Bdr,
+    pub vsens: Bdr,
+}
+
+impl From<BdrChangeData> for SensorData {
+    fn from(value: BdrChangeData) -> Self {
+        let word = |bdr: Bdr| bdr.code() as i16 | (matches!(bdr, Bdr::Gen2(_)) as i16) << 8;
+
+        SensorData {
+            data: [word(value.xl), word(value.gy), word(value.vsens)],
+        }
+    }
+}
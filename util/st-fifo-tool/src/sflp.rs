@@ -0,0 +1,110 @@
+use crate::{F16, QuaternionData, SensorData, Vector3};
+
+/// Gravity vector sensitivity in mg/LSB.
+const GRAVITY_SENSITIVITY_MG: f32 = 0.061;
+/// Gyroscope bias sensitivity in mdps/LSB (±125 dps full scale).
+const GYRO_BIAS_SENSITIVITY_MDPS: f32 = 4.375;
+
+/// Unit quaternion computed by the sensor fusion low-power (SFLP) block.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// Euler angles in degrees (Z-Y-X convention).
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EulerAngles {
+    pub roll: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion { w: 1.0, x: 0.0, y: 0.0, z: 0.0 }
+    }
+}
+
+impl Quaternion {
+    /// Rebuilds the quaternion from the three half-precision vector components.
+    ///
+    /// The scalar part is not stored in the FIFO: it is always positive and is
+    /// computed as `w = sqrt(1 - x² - y² - z²)`. If rounding makes the vector part
+    /// longer than one, it is normalized first and `w` is set to zero.
+    pub fn from_sflp(qx: u16, qy: u16, qz: u16) -> Self {
+        let mut x = F16(qx).to_f32();
+        let mut y = F16(qy).to_f32();
+        let mut z = F16(qz).to_f32();
+        let mut sumsq = x * x + y * y + z * z;
+
+        if sumsq > 1.0 {
+            let n = libm::sqrtf(sumsq);
+            x /= n;
+            y /= n;
+            z /= n;
+            sumsq = 1.0;
+        }
+
+        Quaternion { w: libm::sqrtf(1.0 - sumsq), x, y, z }.normalize()
+    }
+
+    pub fn norm(&self) -> f32 {
+        libm::sqrtf(self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z)
+    }
+
+    pub fn normalize(self) -> Self {
+        let n = self.norm();
+
+        if n == 0.0 {
+            return Quaternion::default();
+        }
+
+        Quaternion {
+            w: self.w / n,
+            x: self.x / n,
+            y: self.y / n,
+            z: self.z / n,
+        }
+    }
+
+    pub fn to_euler(self) -> EulerAngles {
+        let Quaternion { w, x, y, z } = self;
+        let sinp = (2.0 * (w * y - z * x)).clamp(-1.0, 1.0);
+
+        EulerAngles {
+            roll: libm::atan2f(2.0 * (w * x + y * z), 1.0 - 2.0 * (x * x + y * y)).to_degrees(),
+            pitch: libm::asinf(sinp).to_degrees(),
+            yaw: libm::atan2f(2.0 * (w * z + x * y), 1.0 - 2.0 * (y * y + z * z)).to_degrees(),
+        }
+    }
+}
+
+impl QuaternionData {
+    pub fn to_f32(self) -> Quaternion {
+        Quaternion::from_sflp(self.qx, self.qy, self.qz)
+    }
+}
+
+impl SensorData {
+    /// Converts a `Tag::GameRv`, `Tag::GeomRv` or `Tag::NormRv` sample.
+    pub fn to_sflp_quaternion(self) -> Quaternion {
+        self.to_quaternion().to_f32()
+    }
+
+    /// Converts a `Tag::Gravity` sample into mg.
+    pub fn to_gravity(self) -> Vector3 {
+        Vector3::scale(self.data, GRAVITY_SENSITIVITY_MG)
+    }
+
+    /// Converts a `Tag::GyroBias` sample into mdps.
+    pub fn to_gyro_bias(self) -> Vector3 {
+        Vector3::scale(self.data, GYRO_BIAS_SENSITIVITY_MDPS)
+    }
+}
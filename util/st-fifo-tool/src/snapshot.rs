@@ -0,0 +1,190 @@
+//! Snapshot of the decoder state, e.g. to keep it in a retention RAM while the
+//! MCU sleeps between FIFO reads.
+//!
+//! Only the state changed by the decoding is saved: the values derived from the
+//! `Config` come from `FifoData::init`, and the statistics are not saved.
+//! Layout (little-endian, version 1):
+//!
+//! | offset | size | content                                                  |
+//! |--------|------|----------------------------------------------------------|
+//! | 0      | 1    | layout version                                           |
+//! | 1      | 1    | FIFO generation                                          |
+//! | 2      | 1    | tag counter                                              |
+//! | 3      | 1    | flags: ODR change XL/GY, TS synced, XL/GY gap reference  |
+//! | 4      | 1    | auxiliary channels with a last timestamp (bit mask)      |
+//! | 5      | 1    | host anchors and residual present (bit mask)             |
+//! | 6      | 20   | periods: XL, GY, min, XL before ODR change, GY before    |
+//! | 26     | 12   | timestamps: current, last XL, last GY                    |
+//! | 38     | 12   | last XL and GY samples                                   |
+//! | 50     | 16   | gap detection: last XL/GY timestamps and old periods     |
+//! | 66     | 28   | last timestamps of the auxiliary channels                |
+//! | 94     | 12   | last TS word, its correction and the drift               |
+//! | 106    | 32   | host anchors (timestamp, ns) and residual (ns)           |
+//! | 138    | 6    | batch data rates in effect, as a `BdrChange` slot        |
+
+use crate::{FifoData, SensorData, Status};
+
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// Size of a snapshot in bytes.
+pub const SNAPSHOT_SIZE: usize = 144;
+
+struct Writer<'a> {
+    buf: &'a mut [u8; SNAPSHOT_SIZE],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn bytes<const K: usize>(&mut self, bytes: [u8; K]) {
+        self.buf[self.pos..self.pos + K].copy_from_slice(&bytes);
+        self.pos += K;
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn bytes<const K: usize>(&mut self) -> [u8; K] {
+        let bytes = self.buf[self.pos..self.pos + K].try_into().unwrap();
+        self.pos += K;
+        bytes
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.bytes::<1>()[0]
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.bytes())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.bytes())
+    }
+}
+
+impl FifoData {
+    /// Saves the decoding state (see the module documentation for the layout).
+    pub fn snapshot(&self) -> [u8; SNAPSHOT_SIZE] {
+        let mut buf = [0; SNAPSHOT_SIZE];
+        let mut w = Writer { buf: &mut buf, pos: 0 };
+
+        let flags = self.bdr_chg_xl_flag & 1
+            | (self.bdr_chg_gy_flag & 1) << 1
+            | (self.ts_synced as u8) << 2
+            | (self.gap_last[0].is_some() as u8) << 3
+            | (self.gap_last[1].is_some() as u8) << 4;
+        let aux = self.last_timestamp_aux.iter().enumerate().fold(0, |mask, (n, last)| mask | (last.is_some() as u8) << n);
+        let anchors = (self.anchors.last[0].is_some() as u8)
+            | (self.anchors.last[1].is_some() as u8) << 1
+            | (self.anchors.residual_ns.is_some() as u8) << 2;
+
+        w.bytes([SNAPSHOT_VERSION, self.fifo_ver, self.tag_counter_old, flags, aux, anchors]);
+
+        for dtime in [self.dtime_xl, self.dtime_gy, self.dtime_min, self.dtime_xl_old, self.dtime_gy_old] {
+            w.bytes(dtime.to_le_bytes());
+        }
+
+        for timestamp in [self.timestamp, self.last_timestamp_xl, self.last_timestamp_gy] {
+            w.bytes(timestamp.to_le_bytes());
+        }
+
+        for value in self.last_data_xl.iter().chain(&self.last_data_gy) {
+            w.bytes(value.to_le_bytes());
+        }
+
+        for value in self.gap_last.map(|last| last.unwrap_or(0)).iter().chain(&self.gap_dtime_old) {
+            w.bytes(value.to_le_bytes());
+        }
+
+        for last in self.last_timestamp_aux {
+            w.bytes(last.unwrap_or(0).to_le_bytes());
+        }
+
+        w.bytes(self.ts_last.to_le_bytes());
+        w.bytes(self.ts_correction.to_le_bytes());
+        w.bytes(self.ts_drift.to_le_bytes());
+
+        for (timestamp, host_ns) in self.anchors.last.map(|anchor| anchor.unwrap_or((0, 0))) {
+            w.bytes(timestamp.to_le_bytes());
+            w.bytes(host_ns.to_le_bytes());
+        }
+
+        w.bytes(self.anchors.residual_ns.unwrap_or(0).to_le_bytes());
+        w.bytes(SensorData::from(self.bdr).to_bytes());
+
+        buf
+    }
+
+    /// Restores a state saved by `snapshot` into a decoder initialized with the
+    /// same configuration.
+    ///
+    /// Returns `Status::Err`, leaving the decoder unchanged, if the snapshot has
+    /// another size or layout version, or comes from another FIFO generation.
+    pub fn restore(&mut self, snapshot: &[u8]) -> Result<(), Status> {
+        if snapshot.len() != SNAPSHOT_SIZE || snapshot[0] != SNAPSHOT_VERSION || snapshot[1] != self.fifo_ver {
+            return Err(Status::Err);
+        }
+
+        let mut r = Reader { buf: snapshot, pos: 2 };
+        let tag_counter = r.u8();
+        let flags = r.u8();
+        let aux = r.u8();
+        let anchors = r.u8();
+
+        if tag_counter > 3 {
+            return Err(Status::Err);
+        }
+
+        self.tag_counter_old = tag_counter;
+        self.bdr_chg_xl_flag = flags & 1;
+        self.bdr_chg_gy_flag = flags >> 1 & 1;
+        self.ts_synced = flags & 1 << 2 != 0;
+
+        self.dtime_xl = r.u32();
+        self.dtime_gy = r.u32();
+        self.dtime_min = r.u32();
+        self.dtime_xl_old = r.u32();
+        self.dtime_gy_old = r.u32();
+
+        self.timestamp = r.u32();
+        self.last_timestamp_xl = r.u32();
+        self.last_timestamp_gy = r.u32();
+
+        self.last_data_xl = [(); 3].map(|_| i16::from_le_bytes(r.bytes()));
+        self.last_data_gy = [(); 3].map(|_| i16::from_le_bytes(r.bytes()));
+
+        for (k, last) in self.gap_last.iter_mut().enumerate() {
+            let timestamp = r.u32();
+            *last = (flags & 1 << (3 + k) != 0).then_some(timestamp);
+        }
+
+        self.gap_dtime_old = [r.u32(), r.u32()];
+
+        for (n, last) in self.last_timestamp_aux.iter_mut().enumerate() {
+            let timestamp = r.u32();
+            *last = (aux & 1 << n != 0).then_some(timestamp);
+        }
+
+        self.ts_last = r.u32();
+        self.ts_correction = r.u32() as i32;
+        self.ts_drift = r.u32();
+
+        for (k, anchor) in self.anchors.last.iter_mut().enumerate() {
+            let (timestamp, host_ns) = (r.u32(), r.u64());
+            *anchor = (anchors & 1 << k != 0).then_some((timestamp, host_ns));
+        }
+
+        let residual_ns = r.u64();
+        self.anchors.residual_ns = (anchors & 1 << 2 != 0).then_some(residual_ns);
+
+        let mut bdr = SensorData::default();
+        bdr.from_u8_arr(&r.bytes::<6>());
+        self.bdr = bdr.to_bdr_change();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+//! Stable, allocation-free sort used by `FifoData::sort`.
+//!
+//! Insertion sort on small blocks, then the blocks are merged in place with the
+//! SymMerge algorithm (Kim & Kutzner, "Stable Minimum Storage Merging by
+//! Symmetric Comparisons"): O(n log n) comparisons and O(n log² n) moves, no
+//! buffer. Already sorted blocks are not merged, so the nearly sorted output of
+//! the decoder costs O(n).
+
+use crate::{FifoData, OutSlot};
+
+const BLOCK_SIZE: usize = 20;
+
+pub(crate) fn stable_sort_by_key<T, K: Ord>(v: &mut [T], key: impl Fn(&T) -> K + Copy) {
+    let n = v.len();
+    let mut block_size = BLOCK_SIZE;
+
+    for block in v.chunks_mut(block_size) {
+        insertion_sort(block, key);
+    }
+
+    while block_size < n {
+        let mut a = 0;
+
+        while a + block_size < n {
+            let m = a + block_size;
+            let b = (m + block_size).min(n);
+
+            if key(&v[m]) < key(&v[m - 1]) {
+                sym_merge(v, a, m, b, key);
+            }
+
+            a = b;
+        }
+
+        block_size *= 2;
+    }
+}
+
+fn insertion_sort<T, K: Ord>(v: &mut [T], key: impl Fn(&T) -> K) {
+    for i in 1..v.len() {
+        let mut j = i;
+
+        while j > 0 && key(&v[j]) < key(&v[j - 1]) {
+            v.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// Merges the sorted runs `v[a..m]` and `v[m..b]`.
+fn sym_merge<T, K: Ord>(v: &mut [T], a: usize, m: usize, b: usize, key: impl Fn(&T) -> K + Copy) {
+    if m - a == 1 {
+        // insert v[a] after the elements of v[m..b] lower or equal to it
+        let i = m + v[m..b].partition_point(|x| key(x) < key(&v[a]));
+        v[a..i].rotate_left(1);
+        return;
+    }
+
+    if b - m == 1 {
+        // insert v[m] after the elements of v[a..m] lower or equal to it
+        let i = a + v[a..m].partition_point(|x| key(x) <= key(&v[m]));
+        v[i..b].rotate_right(1);
+        return;
+    }
+
+    let mid = (a + b) / 2;
+    let n = mid + m;
+    let (mut start, mut r) = if m > mid { (n - b, mid) } else { (a, m) };
+    let p = n - 1;
+
+    while start < r {
+        let c = (start + r) / 2;
+
+        if key(&v[p - c]) >= key(&v[c]) {
+            start = c + 1;
+        } else {
+            r = c;
+        }
+    }
+
+    let end = n - start;
+
+    if start < m && m < end {
+        v[start..end].rotate_left(m - start);
+    }
+
+    if a < start && start < mid {
+        sym_merge(v, a, start, mid, key);
+    }
+
+    if mid < end && end < b {
+        sym_merge(v, mid, end, b, key);
+    }
+}
+
+impl FifoData {
+    /// Sorts the decoded samples by timestamp; samples with the same timestamp
+    /// keep the decoding order.
+    pub fn sort(&self, fifo_out_slot: &mut [OutSlot], out_slot_size: u16) {
+        stable_sort_by_key(&mut fifo_out_slot[..out_slot_size as usize], |slot| slot.timestamp);
+    }
+
+    /// Merges runs of samples already sorted by timestamp (e.g. the output of
+    /// `extract_sensor` for each sensor) into `fifo_out_slot`, without sorting.
+    ///
+    /// On equal timestamps, the sample of the first run comes first. The merged
+    /// samples are removed from `runs`, so the merge can go on with another
+    /// output buffer when `fifo_out_slot` is full. Returns the number of samples
+    /// written.
+    pub fn merge(&self, runs: &mut [&[OutSlot]], fifo_out_slot: &mut [OutSlot]) -> u16 {
+        let mut j = 0;
+
+        for out_slot in fifo_out_slot.iter_mut() {
+            let next = runs
+                .iter()
+                .enumerate()
+                .filter_map(|(i, run)| run.first().map(|slot| (slot.timestamp, i)))
+                .min();
+
+            let Some((_, i)) = next else {
+                break;
+            };
+
+            *out_slot = runs[i][0];
+            runs[i] = &runs[i][1..];
+            j += 1;
+        }
+
+        j
+    }
+}
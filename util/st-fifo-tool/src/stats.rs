@@ -0,0 +1,39 @@
+use crate::{CompressionType, SensorType};
+
+/// Number of samples packed in a word of each `CompressionType`.
+const SAMPLES_PER_WORD: [u32; 5] = [1, 1, 1, 2, 3];
+
+/// Decoding statistics, updated by `FifoData::decode` and returned by `FifoData::stats`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FifoStats {
+    pub words: u32,            // FIFO words decoded (TS and ODRCHG words included)
+    pub compression: [u32; 5], // accelerometer/gyroscope words, indexed by CompressionType
+    pub gaps: u32,             // DataLoss events
+    pub lost_samples: u32,     // samples (or time slots) lost, when known
+    pub overruns: u32,         // FIFO_OVR flags reported by the caller
+    pub odr_changes: u32,      // ODRCHG words
+    pub(crate) samples: [u32; 32],
+}
+
+impl FifoStats {
+    /// Returns the number of decoded samples of the sensor.
+    pub fn samples(&self, sensor_type: SensorType) -> u32 {
+        self.samples[sensor_type as usize]
+    }
+
+    /// Returns the number of accelerometer/gyroscope words of the compression type.
+    pub fn compressed_words(&self, compression_type: CompressionType) -> u32 {
+        self.compression[compression_type as usize]
+    }
+
+    /// Returns the average number of accelerometer/gyroscope samples per FIFO word
+    /// (1.0 without compression, up to 3.0).
+    pub fn compression_ratio(&self) -> f32 {
+        let words: u32 = self.compression.iter().sum();
+        let samples: u32 = self.compression.iter().zip(SAMPLES_PER_WORD).map(|(n, k)| n * k).sum();
+
+        if words == 0 { 1.0 } else { samples as f32 / words as f32 }
+    }
+}
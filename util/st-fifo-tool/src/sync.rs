@@ -0,0 +1,243 @@
+use crate::{Bdr, Config, DEVICES, OutSlot, STANDARD_GRAVITY, SensorType, Status, Vector3};
+
+/// Accelerometer/gyroscope samples kept to interpolate: it covers a ratio of
+/// 16 between the faster and the slower sensor.
+const HISTORY: usize = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Interpolation {
+    ZeroOrderHold, // last sample at or before the output time
+    Linear,        // between the samples around the output time
+}
+
+/// Accelerometer (m/s²) and gyroscope (dps) sample at timestamp `t`.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ImuSample {
+    pub t: u32,
+    pub accel: Vector3,
+    pub gyro: Vector3,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Sample {
+    t: u32,
+    v: Vector3,
+}
+
+#[derive(Clone, Copy)]
+struct History {
+    buf: [Sample; HISTORY],
+    len: usize,
+    head: usize,
+    enabled: bool,
+}
+
+impl History {
+    const fn new(enabled: bool) -> Self {
+        History {
+            buf: [Sample { t: 0, v: Vector3 { x: 0.0, y: 0.0, z: 0.0 } }; HISTORY],
+            len: 0,
+            head: 0,
+            enabled,
+        }
+    }
+
+    fn push(&mut self, sample: Sample) {
+        self.buf[(self.head + self.len) % HISTORY] = sample;
+
+        if self.len < HISTORY {
+            self.len += 1;
+        } else {
+            self.head = (self.head + 1) % HISTORY;
+        }
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = &Sample> {
+        (0..self.len).map(|i| &self.buf[(self.head + i) % HISTORY])
+    }
+
+    fn first(&self) -> Option<&Sample> {
+        self.iter().next()
+    }
+
+    fn last(&self) -> Option<&Sample> {
+        self.iter().next_back()
+    }
+
+    /// Returns the samples at or before `t` and at or after `t`.
+    fn around(&self, t: u32) -> (Option<&Sample>, Option<&Sample>) {
+        (self.iter().rev().find(|s| s.t <= t), self.iter().find(|s| s.t >= t))
+    }
+}
+
+/// Pairs the accelerometer and gyroscope samples decoded from the FIFO and
+/// resamples them onto a common timeline at the chosen rate.
+///
+/// The decoded samples must be pushed sorted by timestamp (see `FifoData::sort`
+/// and `FifoData::merge`); `pop` returns the `ImuSample`s as soon as they can be
+/// computed. An output is dropped (see `dropped`) when a sensor has no sample
+/// within `max_skew` timestamp ticks from the output time.
+///
+/// The `SensorType::BdrChange` slots of the ODRCHG words are applied by `push`
+/// (see `bdr_change`): while a sensor is off no output is produced, and the
+/// timeline restarts when both are on again.
+#[derive(Clone, Copy)]
+pub struct Synchronizer {
+    period: u32,
+    interpolation: Interpolation,
+    max_skew: u32,
+    k_xl: f32,
+    k_gy: f32,
+    xl: History,
+    gy: History,
+    t_next: Option<u32>,
+    t_stream: u32,
+    dropped: u32,
+}
+
+impl Synchronizer {
+    /// Creates a synchronizer producing samples at `rate` Hz.
+    ///
+    /// Rates of the device BDR table are aligned to the sensor timing (e.g. 120 Hz
+    /// is 384 ticks on the LSM6DSV-class parts), other rates use the time base.
+    pub fn new(conf: &Config, rate: f32, interpolation: Interpolation, max_skew: u32) -> Result<Self, Status> {
+        if rate <= 0.0 {
+            return Err(Status::Err);
+        }
+
+        let device = &DEVICES[conf.device.fifo_ver() as usize];
+        let period = match device.bdr_acc.iter().position(|&bdr| bdr == rate) {
+            Some(idx) => device.dtime[idx],
+            None => (1.0e12 / (rate as f64 * conf.time_base.tick_ps() as f64) + 0.5) as u32,
+        };
+
+        if period == 0 {
+            return Err(Status::Err);
+        }
+
+        Ok(Synchronizer {
+            period,
+            interpolation,
+            max_skew,
+            k_xl: conf.fs_xl.sensitivity_mg() * STANDARD_GRAVITY / 1000.0,
+            k_gy: conf.fs_gy.sensitivity_mdps() / 1000.0,
+            xl: History::new(!conf.bdr_xl.is_off()),
+            gy: History::new(!conf.bdr_gy.is_off()),
+            t_next: None,
+            t_stream: 0,
+            dropped: 0,
+        })
+    }
+
+    /// Returns the output period in timestamp ticks.
+    pub fn period(&self) -> u32 {
+        self.period
+    }
+
+    /// Returns the number of outputs dropped because of the maximum skew.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Adds a decoded sample and applies the rates of a `SensorType::BdrChange`
+    /// slot; the other samples are ignored.
+    pub fn push(&mut self, slot: &OutSlot) {
+        let (history, k) = match slot.sensor_tag {
+            SensorType::Accelerometer => (&mut self.xl, self.k_xl),
+            SensorType::Gyroscope => (&mut self.gy, self.k_gy),
+            SensorType::BdrChange => {
+                let bdr = slot.sensor_data.to_bdr_change();
+                self.bdr_change(bdr.xl, bdr.gy);
+                return;
+            }
+            _ => return,
+        };
+
+        if !history.enabled {
+            return;
+        }
+
+        history.push(Sample {
+            t: slot.timestamp,
+            v: Vector3::scale(slot.sensor_data.data, k),
+        });
+        self.t_stream = self.t_stream.max(slot.timestamp);
+
+        if self.t_next.is_none() {
+            // the timeline starts when both sensors have a sample
+            if let (Some(xl), Some(gy)) = (self.xl.first(), self.gy.first()) {
+                self.t_next = Some(xl.t.max(gy.t));
+            }
+        }
+    }
+
+    /// Returns the next synchronized sample, if it can be computed.
+    pub fn pop(&mut self) -> Option<ImuSample> {
+        loop {
+            let t = self.t_next?;
+
+            if !self.ready(t) {
+                return None;
+            }
+
+            self.t_next = Some(t.wrapping_add(self.period));
+
+            match (self.value_at(&self.xl, t), self.value_at(&self.gy, t)) {
+                (Some(accel), Some(gyro)) => return Some(ImuSample { t, accel, gyro }),
+                _ => self.dropped += 1,
+            }
+        }
+    }
+
+    /// Applies the rates read from an ODRCHG word, when they are not pushed as
+    /// a `SensorType::BdrChange` slot.
+    pub fn bdr_change(&mut self, bdr_xl: Bdr, bdr_gy: Bdr) {
+        let (xl_on, gy_on) = (!bdr_xl.is_off(), !bdr_gy.is_off());
+
+        if xl_on != self.xl.enabled || gy_on != self.gy.enabled {
+            // a sensor turned off or on: restart the timeline
+            self.xl = History::new(xl_on);
+            self.gy = History::new(gy_on);
+            self.t_next = None;
+        }
+    }
+
+    fn ready(&self, t: u32) -> bool {
+        let (Some(xl), Some(gy)) = (self.xl.last(), self.gy.last()) else {
+            return false;
+        };
+
+        match self.interpolation {
+            // no sample at or before t can come anymore (samples are sorted)
+            Interpolation::ZeroOrderHold => self.t_stream > t || (xl.t >= t && gy.t >= t),
+            Interpolation::Linear => xl.t >= t && gy.t >= t,
+        }
+    }
+
+    fn value_at(&self, history: &History, t: u32) -> Option<Vector3> {
+        let skew = |s: &Sample| s.t.abs_diff(t) <= self.max_skew;
+
+        match (self.interpolation, history.around(t)) {
+            (Interpolation::ZeroOrderHold, (Some(lo), _)) if skew(lo) => Some(lo.v),
+            (Interpolation::Linear, (Some(lo), Some(hi))) if skew(lo) || skew(hi) => {
+                if hi.t == lo.t {
+                    return Some(lo.v);
+                }
+
+                let a = (t - lo.t) as f32 / (hi.t - lo.t) as f32;
+                Some(Vector3 {
+                    x: lo.v.x + (hi.v.x - lo.v.x) * a,
+                    y: lo.v.y + (hi.v.y - lo.v.y) * a,
+                    z: lo.v.z + (hi.v.z - lo.v.z) * a,
+                })
+            }
+            // the samples before t went out of the history: hold the next one
+            (Interpolation::Linear, (None, Some(hi))) if skew(hi) => Some(hi.v),
+            _ => None,
+        }
+    }
+}
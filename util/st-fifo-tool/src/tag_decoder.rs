@@ -0,0 +1,81 @@
+//! Decoding of FIFO tags unknown to the crate (ISPU outputs, new virtual
+//! sensors, ...).
+//!
+//! The application registers the tag codes in `Config::custom_tags` and
+//! implements `TagDecoder` with its own sample type. The words with a
+//! registered tag are timed with the tag counter like the other words and
+//! passed to the decoder given to `FifoData::decode_custom`, which delivers the
+//! samples to a callback; `decode` skips them.
+
+use crate::{SensorType, Status};
+
+/// Maximum number of registered tags.
+pub const CUSTOM_TAGS: usize = 4;
+
+/// Timing context of a word with a registered tag.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TagContext {
+    pub tag: u8,                 // tag code
+    pub sensor_type: SensorType, // SensorType::Custom0..3 of the registry entry
+    pub tag_counter: u8,         // 2-bit tag counter of the word
+    pub fifo_ver: u8,            // FIFO generation of the device
+    pub timestamp: u32,          // time slot of the word
+    pub dtime_min: u32,          // time slot length, in timestamp ticks
+}
+
+/// Decoder of the FIFO tags the crate does not know.
+///
+/// A decoder can handle several registered tags (see `TagContext::tag`) and
+/// keep a state across the words.
+pub trait TagDecoder {
+    /// Sample type of the application.
+    type Sample;
+
+    /// Decodes the 6-byte payload of a word; returns `None` to drop the word.
+    fn decode(&mut self, payload: &[u8; 6], ctx: &TagContext) -> Option<Self::Sample>;
+}
+
+/// Tag codes registered by the application, see `TagDecoder`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TagRegistry {
+    tags: [Option<u8>; CUSTOM_TAGS],
+}
+
+impl TagRegistry {
+    pub const fn new() -> Self {
+        TagRegistry { tags: [None; CUSTOM_TAGS] }
+    }
+
+    /// Registers a tag code and returns the sensor type of its samples, or an
+    /// error if the tag is already registered or the registry is full.
+    ///
+    /// `FifoData::init` rejects the tags known to the device.
+    pub fn register(&mut self, tag: u8) -> Result<SensorType, Status> {
+        if self.get(tag).is_some() {
+            return Err(Status::Err);
+        }
+
+        let n = self.tags.iter().position(Option::is_none).ok_or(Status::Err)?;
+        self.tags[n] = Some(tag);
+
+        Ok(Self::sensor_type(n))
+    }
+
+    /// Returns the registered tag codes.
+    pub fn tags(&self) -> impl Iterator<Item = u8> + '_ {
+        self.tags.iter().flatten().copied()
+    }
+
+    /// Returns the sensor type of a tag code, if registered.
+    pub(crate) fn get(&self, tag: u8) -> Option<SensorType> {
+        self.tags.iter().position(|code| *code == Some(tag)).map(Self::sensor_type)
+    }
+
+    fn sensor_type(n: usize) -> SensorType {
+        SensorType::try_from(SensorType::Custom0 as u8 + n as u8).unwrap()
+    }
+}
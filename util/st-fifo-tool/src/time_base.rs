@@ -0,0 +1,65 @@
+use core::time::Duration;
+
+use crate::DeviceType;
+
+/// Converts the FIFO timestamp ticks into wall-clock time.
+///
+/// One timestamp LSB is 25 µs on the LSM6DSO-class parts and 21.75 µs on the
+/// LSM6DSV-class parts. The real period depends on the internal oscillator and
+/// is corrected by the FREQ_FINE trimming register of the sensor:
+///
+/// `tick = nominal / (1 + k * freq_fine)`
+///
+/// where `k` is 0.0015 on the fifo_ver 0 parts and 0.0013 on the fifo_ver 1 and 2
+/// parts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimeBase {
+    pub nominal_tick_ps: u32, // nominal timestamp LSB in picoseconds
+    pub trim_ppm: u32,        // FREQ_FINE weight in ppm per LSB
+    pub freq_fine: Option<i8>, // FREQ_FINE register content (if read from the sensor)
+}
+
+impl TimeBase {
+    /// Creates the nominal time base of the device, without trimming.
+    pub const fn new(device: DeviceType) -> Self {
+        match device.fifo_ver() {
+            0 => TimeBase {
+                nominal_tick_ps: 25_000_000,
+                trim_ppm: 1500,
+                freq_fine: None,
+            },
+            _ => TimeBase {
+                nominal_tick_ps: 21_750_000,
+                trim_ppm: 1300,
+                freq_fine: None,
+            },
+        }
+    }
+
+    /// Applies the FREQ_FINE value read from the sensor.
+    pub const fn with_freq_fine(mut self, freq_fine: i8) -> Self {
+        self.freq_fine = Some(freq_fine);
+        self
+    }
+
+    /// Returns the timestamp LSB in picoseconds, trimmed if `freq_fine` is set.
+    pub fn tick_ps(&self) -> u64 {
+        match self.freq_fine {
+            Some(freq_fine) => {
+                let den = 1_000_000 + self.trim_ppm as i64 * freq_fine as i64;
+                (self.nominal_tick_ps as i64 * 1_000_000 / den) as u64
+            }
+            None => self.nominal_tick_ps as u64,
+        }
+    }
+
+    pub fn ticks_to_ns(&self, ticks: u32) -> u64 {
+        ticks as u64 * self.tick_ps() / 1000
+    }
+
+    pub fn ticks_to_duration(&self, ticks: u32) -> Duration {
+        Duration::from_nanos(self.ticks_to_ns(ticks))
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{Config, DeviceType, OutSlot, Quaternion, SensorType};
+
+/// Standard gravity in m/s².
+pub const STANDARD_GRAVITY: f32 = 9.80665;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum XlFullScale {
+    G2,
+    G4,
+    G8,
+    G16,
+    G32,
+}
+
+impl XlFullScale {
+    /// Returns the sensitivity in mg/LSB.
+    pub const fn sensitivity_mg(&self) -> f32 {
+        match self {
+            XlFullScale::G2 => 0.061,
+            XlFullScale::G4 => 0.122,
+            XlFullScale::G8 => 0.244,
+            XlFullScale::G16 => 0.488,
+            XlFullScale::G32 => 0.976,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GyFullScale {
+    Dps125,
+    Dps250,
+    Dps500,
+    Dps1000,
+    Dps2000,
+    Dps4000,
+}
+
+impl GyFullScale {
+    /// Returns the sensitivity in mdps/LSB.
+    pub const fn sensitivity_mdps(&self) -> f32 {
+        match self {
+            GyFullScale::Dps125 => 4.375,
+            GyFullScale::Dps250 => 8.75,
+            GyFullScale::Dps500 => 17.5,
+            GyFullScale::Dps1000 => 35.0,
+            GyFullScale::Dps2000 => 70.0,
+            GyFullScale::Dps4000 => 140.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HgFullScale {
+    G32,
+    G64,
+    G80,
+    G128,
+    G256,
+    G320,
+}
+
+impl HgFullScale {
+    /// Returns the sensitivity in mg/LSB.
+    pub const fn sensitivity_mg(&self) -> f32 {
+        match self {
+            HgFullScale::G32 => 0.976,
+            HgFullScale::G64 => 1.952,
+            HgFullScale::G80 => 2.441,
+            HgFullScale::G128 => 3.904,
+            HgFullScale::G256 => 7.808,
+            HgFullScale::G320 => 10.417,
+        }
+    }
+}
+
+/// Conversion tables of a device: supported full scales (lowest first) and
+/// temperature sensor characteristics.
+pub struct Units {
+    pub xl_fs: &'static [XlFullScale],
+    pub gy_fs: &'static [GyFullScale],
+    pub hg_fs: &'static [HgFullScale], // empty if there is no high-g accelerometer
+    pub temp_sensitivity: f32, // LSB/°C
+    pub temp_offset: f32,      // °C at 0 LSB
+}
+
+const XL_FS_16G: [XlFullScale; 4] = [XlFullScale::G2, XlFullScale::G4, XlFullScale::G8, XlFullScale::G16];
+const XL_FS_32G: [XlFullScale; 4] = [XlFullScale::G4, XlFullScale::G8, XlFullScale::G16, XlFullScale::G32];
+const GY_FS_2000DPS: [GyFullScale; 5] = [
+    GyFullScale::Dps125,
+    GyFullScale::Dps250,
+    GyFullScale::Dps500,
+    GyFullScale::Dps1000,
+    GyFullScale::Dps2000,
+];
+const GY_FS_4000DPS: [GyFullScale; 6] = [
+    GyFullScale::Dps125,
+    GyFullScale::Dps250,
+    GyFullScale::Dps500,
+    GyFullScale::Dps1000,
+    GyFullScale::Dps2000,
+    GyFullScale::Dps4000,
+];
+const HG_FS_80G: [HgFullScale; 3] = [HgFullScale::G32, HgFullScale::G64, HgFullScale::G80];
+const HG_FS_256G: [HgFullScale; 4] = [HgFullScale::G32, HgFullScale::G64, HgFullScale::G128, HgFullScale::G256];
+const HG_FS_320G: [HgFullScale; 5] = [
+    HgFullScale::G32,
+    HgFullScale::G64,
+    HgFullScale::G128,
+    HgFullScale::G256,
+    HgFullScale::G320,
+];
+
+const UNITS_16G_4000DPS: Units = Units {
+    xl_fs: &XL_FS_16G,
+    gy_fs: &GY_FS_4000DPS,
+    hg_fs: &[],
+    temp_sensitivity: 256.0,
+    temp_offset: 25.0,
+};
+
+const UNITS_16G_2000DPS: Units = Units {
+    xl_fs: &XL_FS_16G,
+    gy_fs: &GY_FS_2000DPS,
+    hg_fs: &[],
+    temp_sensitivity: 256.0,
+    temp_offset: 25.0,
+};
+
+const UNITS_32G_2000DPS: Units = Units {
+    xl_fs: &XL_FS_32G,
+    gy_fs: &GY_FS_2000DPS,
+    hg_fs: &[],
+    temp_sensitivity: 256.0,
+    temp_offset: 25.0,
+};
+
+const UNITS_32G_4000DPS: Units = Units {
+    xl_fs: &XL_FS_32G,
+    gy_fs: &GY_FS_4000DPS,
+    hg_fs: &[],
+    temp_sensitivity: 256.0,
+    temp_offset: 25.0,
+};
+
+const UNITS_80G_HG: Units = Units {
+    xl_fs: &XL_FS_16G,
+    gy_fs: &GY_FS_4000DPS,
+    hg_fs: &HG_FS_80G,
+    temp_sensitivity: 256.0,
+    temp_offset: 25.0,
+};
+
+const UNITS_256G_HG: Units = Units {
+    xl_fs: &XL_FS_16G,
+    gy_fs: &GY_FS_4000DPS,
+    hg_fs: &HG_FS_256G,
+    temp_sensitivity: 256.0,
+    temp_offset: 25.0,
+};
+
+const UNITS_320G_HG: Units = Units {
+    xl_fs: &XL_FS_16G,
+    gy_fs: &GY_FS_4000DPS,
+    hg_fs: &HG_FS_320G,
+    temp_sensitivity: 256.0,
+    temp_offset: 25.0,
+};
+
+impl DeviceType {
+    /// Returns the conversion tables of the device.
+    pub const fn units(&self) -> &'static Units {
+        match self {
+            DeviceType::Lsm6dsr
+            | DeviceType::Lsm6dsrx
+            | DeviceType::Asm330lhh
+            | DeviceType::Asm330lhhx
+            | DeviceType::Ism330dhcx
+            | DeviceType::Lsm6dsv
+            | DeviceType::Lsm6dsv16x => &UNITS_16G_4000DPS,
+            DeviceType::Lsm6dso | DeviceType::Lsm6dsox | DeviceType::Lsm6dsl | DeviceType::Ism330dlc => &UNITS_16G_2000DPS,
+            DeviceType::Lsm6dso32 | DeviceType::Lsm6dso32x => &UNITS_32G_2000DPS,
+            DeviceType::Lsm6dsv32x => &UNITS_32G_4000DPS,
+            DeviceType::Lsm6dsv80x => &UNITS_80G_HG,
+            DeviceType::Lsm6dsv320x => &UNITS_320G_HG,
+            DeviceType::Ism6hg256x => &UNITS_256G_HG,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vector3 {
+    pub(crate) fn scale(raw: [i16; 3], k: f32) -> Self {
+        Vector3 {
+            x: raw[0] as f32 * k,
+            y: raw[1] as f32 * k,
+            z: raw[2] as f32 * k,
+        }
+    }
+}
+
+/// Sample converted into physical units.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Measurement {
+    Acceleration(Vector3), // m/s²
+    AngularRate(Vector3),  // dps
+    Temperature(f32),      // °C
+    StepCount(u16),
+    Quaternion(Quaternion),
+    Gravity(Vector3),  // m/s²
+    GyroBias(Vector3), // dps
+    MagneticField(Vector3), // µT
+    Pressure { pressure: f32, temperature: f32 }, // hPa, °C
+    Unsupported,
+}
+
+impl Measurement {
+    /// Returns the acceleration in mg, if the measurement is an acceleration.
+    pub fn acceleration_mg(&self) -> Option<Vector3> {
+        match self {
+            Measurement::Acceleration(a) => Some(Vector3 {
+                x: a.x * 1000.0 / STANDARD_GRAVITY,
+                y: a.y * 1000.0 / STANDARD_GRAVITY,
+                z: a.z * 1000.0 / STANDARD_GRAVITY,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl OutSlot {
+    /// Converts the sample into physical units using the full scales and the
+    /// sensor-hub slot formats set in `conf`.
+    pub fn to_si(&self, conf: &Config) -> Measurement {
+        match self.sensor_tag {
+            SensorType::Accelerometer => {
+                let k = conf.fs_xl.sensitivity_mg() * STANDARD_GRAVITY / 1000.0;
+                Measurement::Acceleration(Vector3::scale(self.sensor_data.data, k))
+            }
+            SensorType::HighGAccel => match conf.fs_hg {
+                Some(fs_hg) => {
+                    let k = fs_hg.sensitivity_mg() * STANDARD_GRAVITY / 1000.0;
+                    Measurement::Acceleration(Vector3::scale(self.sensor_data.data, k))
+                }
+                None => Measurement::Unsupported,
+            },
+            SensorType::DualAccel => {
+                let k = conf.fs_dualc.sensitivity_mg() * STANDARD_GRAVITY / 1000.0;
+                Measurement::Acceleration(Vector3::scale(self.sensor_data.data, k))
+            }
+            SensorType::Gyroscope => {
+                let k = conf.fs_gy.sensitivity_mdps() / 1000.0;
+                Measurement::AngularRate(Vector3::scale(self.sensor_data.data, k))
+            }
+            SensorType::EisGyro => {
+                let k = conf.fs_eis.sensitivity_mdps() / 1000.0;
+                Measurement::AngularRate(Vector3::scale(self.sensor_data.data, k))
+            }
+            SensorType::Temperature => Measurement::Temperature(self.sensor_data.to_temperature().celsius(conf.device)),
+            SensorType::StepCounter => Measurement::StepCount(self.sensor_data.to_step_counter().steps),
+            SensorType::GameRv6x | SensorType::GeomRv6x | SensorType::Rv9x => {
+                Measurement::Quaternion(self.sensor_data.to_sflp_quaternion())
+            }
+            SensorType::Gravity => {
+                let g = self.sensor_data.to_gravity();
+                let k = STANDARD_GRAVITY / 1000.0;
+                Measurement::Gravity(Vector3 { x: g.x * k, y: g.y * k, z: g.z * k })
+            }
+            SensorType::GyroBias => {
+                let b = self.sensor_data.to_gyro_bias();
+                Measurement::GyroBias(Vector3 { x: b.x / 1000.0, y: b.y / 1000.0, z: b.z / 1000.0 })
+            }
+            SensorType::ExtSensor0 | SensorType::ExtSensor1 | SensorType::ExtSensor2 | SensorType::ExtSensor3 => {
+                let n = self.sensor_tag as usize - SensorType::ExtSensor0 as usize;
+
+                match conf.ext_sensors[n] {
+                    Some(slot) => slot.format.decode(&self.sensor_data.to_bytes()),
+                    None => Measurement::Unsupported,
+                }
+            }
+            _ => Measurement::Unsupported,
+        }
+    }
+}
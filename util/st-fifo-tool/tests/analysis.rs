@@ -0,0 +1,74 @@
+#![cfg(feature = "std")]
+
+use st_fifo_tool::analysis::*;
+use st_fifo_tool::*;
+
+const DTIME_104HZ: u32 = 384; // 25 us ticks
+
+fn slot(sensor_tag: SensorType, timestamp: u32, data: [i16; 3]) -> OutSlot {
+    OutSlot { timestamp, sensor_tag, sensor_data: SensorData { data } }
+}
+
+fn config() -> Config {
+    Config {
+        bdr_xl: BdrGen1::Hz104.into(),
+        bdr_gy: BdrGen1::Hz104.into(),
+        ..Config::new(DeviceType::Lsm6dso)
+    }
+}
+
+#[test]
+fn odr_and_dropped_samples() {
+    let samples: Vec<_> = (0..100u32)
+        .filter(|i| !(40..43).contains(i))
+        .map(|i| slot(SensorType::Accelerometer, i * DTIME_104HZ, [0; 3]))
+        .collect();
+
+    let report = odr(&samples, &config(), SensorType::Accelerometer).unwrap();
+
+    assert_eq!(report.samples, 97);
+    assert_eq!(report.dropped, 3);
+    assert_eq!(report.nominal_hz, 104.0);
+    // 384 ticks of 25 us is 104.17 Hz
+    assert!((report.effective_hz - 1.0 / 9.6e-3).abs() < 1e-6);
+    assert!((report.error_ppm - 1602.6).abs() < 0.1);
+    assert_eq!(report.jitter_rms_us, 0.0);
+
+    assert_eq!(odr(&samples, &config(), SensorType::Temperature), None);
+}
+
+#[test]
+fn jitter() {
+    // intervals of 388 and 380 ticks: 100 us from the mean
+    let samples: Vec<_> =
+        (0..101u32).map(|i| slot(SensorType::Gyroscope, i * DTIME_104HZ + (i % 2) * 4, [0; 3])).rev().collect();
+
+    let report = odr(&samples, &config(), SensorType::Gyroscope).unwrap();
+
+    assert_eq!(report.dropped, 0);
+    assert!((report.jitter_rms_us - 100.0).abs() < 1e-6);
+    assert!((report.jitter_max_us - 100.0).abs() < 1e-6);
+}
+
+#[test]
+fn noise_and_allan_deviation() {
+    // x alternates between 90 and 110 LSB, 4.375 mdps each
+    let samples: Vec<_> = (0..64u32)
+        .map(|i| slot(SensorType::Gyroscope, i * DTIME_104HZ, [if i % 2 == 0 { 90 } else { 110 }, -200, 0]))
+        .collect();
+    let conf = config();
+
+    let report = noise(&samples, &conf, SensorType::Gyroscope).unwrap();
+    assert_eq!(report.samples, 64);
+    assert!((report.mean[0] - 0.4375).abs() < 1e-6);
+    assert!((report.mean[1] + 0.875).abs() < 1e-6);
+    assert!((report.rms[0] - 0.04375).abs() < 1e-6);
+    assert!(report.rms[1].abs() < 1e-6);
+
+    // the alternation averages out from 2 samples on
+    let points = allan_deviation(&samples, &conf, SensorType::Gyroscope);
+    assert_eq!(points.len(), 5);
+    assert!((points[0].tau - 1.0 / 104.0).abs() < 1e-9);
+    assert!((points[0].adev[0] - 0.04375 * 2f64.sqrt()).abs() < 1e-6);
+    assert!(points[1..].iter().all(|p| p.adev[0] < 1e-6 && p.adev[1] < 1e-6));
+}
@@ -0,0 +1,58 @@
+use st_fifo_tool::*;
+
+#[test]
+fn codes() {
+    for code in 0..16 {
+        if let Some(bdr) = Bdr::from_code(DeviceType::Lsm6dsv16x, code) {
+            assert_eq!(bdr.code(), code);
+            assert_eq!(bdr.hz(), DEVICES[1].bdr_acc[code as usize]);
+        }
+    }
+
+    assert_eq!(Bdr::from_code(DeviceType::Lsm6dso, 8), Some(BdrGen1::Hz1666.into()));
+    assert_eq!(Bdr::from_code(DeviceType::Lsm6dsv16x, 12), Some(BdrGen2::Hz7680.into()));
+    assert_eq!(Bdr::from_code(DeviceType::Lsm6dsv16x, 0), Some(Bdr::Off));
+    assert_eq!(Bdr::from_code(DeviceType::Lsm6dsv16x, 13), None);
+}
+
+#[test]
+fn dtime() {
+    assert_eq!(Bdr::Off.dtime(DeviceType::Lsm6dso), Ok(0));
+    assert_eq!(Bdr::from(BdrGen1::Hz104).dtime(DeviceType::Lsm6dso), Ok(384));
+    assert_eq!(Bdr::from(BdrGen1::Hz1_625).dtime(DeviceType::Lsm6dso), Ok(24576));
+    assert_eq!(Bdr::from(BdrGen2::Hz120).dtime(DeviceType::Lsm6dsv16x), Ok(384));
+    assert_eq!(Bdr::from(BdrGen1::Hz104).dtime(DeviceType::Lsm6dsv16x), Err(Status::Err));
+
+    // the high-g rates have the accelerometer codes, not their index in the bdr_hg table
+    let hg = Bdr::from(BdrGen2::Hz480);
+    assert_eq!(hg.code(), 8);
+    assert_eq!(DEVICES[2].bdr_hg[3], 480.0);
+    assert_eq!(hg.dtime(DeviceType::Lsm6dsv320x), Ok(96));
+}
+
+#[test]
+fn from_hz() {
+    assert_eq!(Bdr::from_hz(DeviceType::Lsm6dso, 1666.0), Ok(BdrGen1::Hz1666.into()));
+    assert_eq!(Bdr::from_hz(DeviceType::Lsm6dsv320x, 1.875), Ok(BdrGen2::Hz1_875.into()));
+    assert_eq!(Bdr::from_hz(DeviceType::Lsm6dso, 0.0), Ok(Bdr::Off));
+    assert_eq!(Bdr::from_hz(DeviceType::Lsm6dso, 1600.0), Err(Status::Err));
+    assert_eq!(Bdr::from_hz(DeviceType::Lsm6dsv16x, 1666.0), Err(Status::Err));
+}
+
+#[test]
+fn unsupported_rates() {
+    // rate of another generation
+    let conf = Config { bdr_xl: BdrGen1::Hz104.into(), ..Config::new(DeviceType::Lsm6dsv16x) };
+    assert!(FifoData::init(&conf).is_err());
+
+    // the gyroscope cannot batch at 1.625 Hz
+    let conf = Config { bdr_gy: BdrGen1::Hz1_625.into(), ..Config::new(DeviceType::Lsm6dso) };
+    assert!(FifoData::init(&conf).is_err());
+
+    // the high-g accelerometer starts at 480 Hz
+    let conf = Config { bdr_hg: BdrGen2::Hz240.into(), ..Config::new(DeviceType::Lsm6dsv320x) };
+    assert!(FifoData::init(&conf).is_err());
+
+    let conf = Config { bdr_hg: BdrGen2::Hz480.into(), ..Config::new(DeviceType::Lsm6dsv320x) };
+    assert!(FifoData::init(&conf).is_ok());
+}
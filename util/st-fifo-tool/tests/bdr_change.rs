@@ -0,0 +1,79 @@
+use st_fifo_tool::*;
+
+const TAG_GY: u8 = 0x01;
+const TAG_XL: u8 = 0x02;
+const TAG_ODRCHG: u8 = 0x05;
+
+fn word(tag: u8, counter: u8, payload: [u8; 6]) -> RawSlot {
+    let mut fifo_data_out = [0; 7];
+    fifo_data_out[0] = tag << 3 | counter << 1;
+    fifo_data_out[1..].copy_from_slice(&payload);
+    RawSlot { fifo_data_out }
+}
+
+fn odrchg(counter: u8, bdr_xl: BdrGen2, bdr_gy: BdrGen2) -> RawSlot {
+    word(TAG_ODRCHG, counter, [0, 0, 0, 0, 0, u8::from(bdr_gy) << 4 | u8::from(bdr_xl)])
+}
+
+fn config() -> Config {
+    Config {
+        bdr_xl: BdrGen2::Hz120.into(),
+        bdr_gy: BdrGen2::Hz120.into(),
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    }
+}
+
+#[test]
+fn bdr_change_event() {
+    let mut fifo = FifoData::init(&config()).unwrap();
+    assert_eq!(
+        fifo.bdr(),
+        BdrChangeData { xl: BdrGen2::Hz120.into(), gy: BdrGen2::Hz120.into(), vsens: Bdr::Off }
+    );
+
+    let raw = [
+        word(TAG_XL, 0, [1, 0, 2, 0, 3, 0]),
+        word(TAG_GY, 0, [4, 0, 5, 0, 6, 0]),
+        odrchg(0, BdrGen2::Hz240, BdrGen2::Hz120),
+        word(TAG_XL, 1, [7, 0, 8, 0, 9, 0]),
+    ];
+    let mut out = [OutSlot::default(); 8];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode_with_overrun(&mut out, &raw, &mut out_size, raw.len() as u16, false), Status::Ok);
+
+    let tags: Vec<_> = out[..out_size as usize].iter().map(|s| s.sensor_tag).collect();
+    assert_eq!(tags, [SensorType::Accelerometer, SensorType::Gyroscope, SensorType::BdrChange, SensorType::Accelerometer]);
+
+    let expected = BdrChangeData { xl: BdrGen2::Hz240.into(), gy: BdrGen2::Hz120.into(), vsens: Bdr::Off };
+    assert_eq!(out[2].timestamp, 0);
+    assert_eq!(out[2].sensor_data.to_bdr_change(), expected);
+    assert_eq!(out[2].payload(), Payload::BdrChange(expected));
+    assert_eq!(fifo.bdr(), expected);
+    assert_eq!(fifo.stats().odr_changes, 1);
+
+    // the next accelerometer sample is timed with the new period (21.75 us ticks)
+    assert_eq!(out[3].timestamp, 192);
+
+    // the rates in effect survive a snapshot
+    let mut restored = FifoData::init(&config()).unwrap();
+    restored.restore(&fifo.snapshot()).unwrap();
+    assert_eq!(restored.bdr(), expected);
+}
+
+#[test]
+fn bdr_change_needs_room() {
+    let mut fifo = FifoData::init(&config()).unwrap();
+    let raw = [word(TAG_XL, 0, [0; 6]), odrchg(0, BdrGen2::Hz60, BdrGen2::Hz60)];
+    let mut out = [OutSlot::default(); 1];
+    let mut out_size = 0;
+
+    assert_eq!(fifo.decode_with_overrun(&mut out, &raw, &mut out_size, raw.len() as u16, false), Status::Err);
+    assert_eq!(out_size, 1);
+    assert_eq!(fifo.stats().odr_changes, 0);
+
+    // decode outputs the samples only
+    let mut fifo = FifoData::init(&config()).unwrap();
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, raw.len() as u16), Status::Ok);
+    assert_eq!(out_size, 1);
+    assert_eq!(fifo.bdr().xl, BdrGen2::Hz60.into());
+}
@@ -0,0 +1,154 @@
+use st_fifo_tool::*;
+
+const T0: u32 = 1_000_000;
+const DTIME_120HZ: u32 = 384;
+
+fn conf() -> Config {
+    Config { bdr_xl: BdrGen2::Hz120.into(), ..Config::new(DeviceType::Lsm6dsv16x) }
+}
+
+/// Encodes `n` accelerometer samples at 120 Hz, one FIFO word each.
+fn encode(n: u32, ts_decimation: TsDecimation) -> Vec<RawSlot> {
+    let samples: Vec<_> = (0..n)
+        .map(|i| OutSlot {
+            timestamp: T0 + i * DTIME_120HZ,
+            sensor_tag: SensorType::Accelerometer,
+            sensor_data: SensorData { data: [i as i16, 0, 0] },
+        })
+        .collect();
+
+    let mut encoder = FifoEncoder::new(&conf(), false, ts_decimation).unwrap();
+    let mut raw = vec![RawSlot::default(); n as usize * 2];
+    let len = encoder.encode(&samples, &mut raw).unwrap() as usize;
+    raw.truncate(len);
+    raw
+}
+
+fn decode(fifo: &mut FifoData, raw: &[RawSlot], fifo_ovr: bool) -> Vec<OutSlot> {
+    let mut out = vec![OutSlot::default(); raw.len() * 3 + 1];
+    let mut out_size = 0;
+
+    assert_eq!(fifo.decode_with_overrun(&mut out, raw, &mut out_size, raw.len() as u16, fifo_ovr), Status::Ok);
+    out.truncate(out_size as usize);
+    out
+}
+
+fn data_loss(out: &[OutSlot]) -> Vec<(u32, DataLossData)> {
+    out.iter()
+        .filter(|s| s.sensor_tag == SensorType::DataLoss)
+        .map(|s| (s.timestamp, s.sensor_data.to_data_loss()))
+        .collect()
+}
+
+#[test]
+fn no_loss() {
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let out = decode(&mut fifo, &encode(40, TsDecimation::X8), false);
+
+    assert!(data_loss(&out).is_empty());
+    assert_eq!(fifo.stats().samples(SensorType::Accelerometer), 40);
+    assert_eq!(fifo.stats().gaps, 0);
+    assert_eq!(fifo.stats().compression_ratio(), 1.0);
+}
+
+#[test]
+fn skipped_words() {
+    let mut raw = encode(20, TsDecimation::Off);
+    // TS word + 5 samples, then 2 samples are lost
+    raw.drain(6..8);
+
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let out = decode(&mut fifo, &raw, false);
+
+    assert_eq!(
+        data_loss(&out),
+        [(
+            T0 + 5 * DTIME_120HZ,
+            DataLossData {
+                cause: DataLossCause::TagCounter,
+                sensor_type: SensorType::Accelerometer,
+                lost_samples: 2,
+            }
+        )]
+    );
+    // the timestamps after the gap are still right
+    assert_eq!(out.last().unwrap().timestamp, T0 + 19 * DTIME_120HZ);
+    assert_eq!(fifo.stats().samples(SensorType::Accelerometer), 18);
+    assert_eq!(fifo.stats().lost_samples, 2);
+}
+
+#[test]
+fn counter_wrap_detected_by_ts() {
+    let mut raw = encode(20, TsDecimation::X8);
+    // words: TS, 8 samples, TS, 8 samples, TS, ... lose 4 samples: the 2-bit
+    // counter wraps around and the loss only shows at the next TS word
+    raw.drain(2..6);
+
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let out = decode(&mut fifo, &raw, false);
+    let loss = data_loss(&out);
+
+    assert_eq!(loss.len(), 1);
+    assert_eq!(loss[0].1.cause, DataLossCause::Timestamp);
+    assert_eq!(loss[0].1.lost_samples, 4);
+    assert_eq!(fifo.stats().gaps, 1);
+}
+
+#[test]
+fn overrun() {
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let out = decode(&mut fifo, &encode(10, TsDecimation::X8), true);
+
+    assert_eq!(out[0].sensor_tag, SensorType::DataLoss);
+    assert_eq!(out[0].sensor_data.to_data_loss().cause, DataLossCause::Overrun);
+    assert_eq!(fifo.stats().overruns, 1);
+
+    fifo.reset_stats();
+    assert_eq!(*fifo.stats(), FifoStats::default());
+}
+
+fn word(tag: u8, counter: u8) -> RawSlot {
+    RawSlot { fifo_data_out: [tag << 3 | counter << 1, 0, 0, 0, 0, 0, 0] }
+}
+
+#[test]
+fn gap_before_compressed_word() {
+    // samples at 0 and 1 time slot, then a 3x compressed word at 3 to 5: the
+    // sample at 2 is lost
+    let raw = [
+        word(Tag::Xl as u8, 0),
+        word(Tag::XlUncompressedT1 as u8, 2),
+        word(Tag::XlCompressed3x as u8, 1),
+    ];
+    let loss = (
+        2 * DTIME_120HZ,
+        DataLossData { cause: DataLossCause::TagCounter, sensor_type: SensorType::Accelerometer, lost_samples: 1 },
+    );
+
+    // 3 slots per word
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let out = decode(&mut fifo, &raw, false);
+    assert_eq!(data_loss(&out), [loss]);
+    assert_eq!(out.len(), 6);
+
+    // room for the samples but not for the DataLoss slot: an error, not a dropped slot
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let mut out = [OutSlot::default(); 5];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode_with_overrun(&mut out, &raw, &mut out_size, raw.len() as u16, false), Status::Err);
+    assert_eq!(out_size, 2);
+
+    // decode outputs the samples only: a buffer sized to the samples is enough
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let mut out_size = 0;
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, raw.len() as u16), Status::Ok);
+    assert_eq!(out_size, 5);
+    assert!(data_loss(&out).is_empty());
+    assert_eq!(fifo.stats().lost_samples, 1);
+
+    // the overrun slot needs room too
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let mut out_size = 0;
+    assert_eq!(fifo.decode_with_overrun(&mut [], &raw, &mut out_size, raw.len() as u16, true), Status::Err);
+    assert_eq!(fifo.stats().overruns, 0);
+}
@@ -0,0 +1,73 @@
+use st_fifo_tool::*;
+
+const DTIME_120HZ: u32 = 384;
+
+fn slot(sensor_tag: SensorType, timestamp: u32) -> OutSlot {
+    OutSlot { timestamp, sensor_tag, sensor_data: SensorData { data: [timestamp as i16, 0, 0] } }
+}
+
+#[test]
+fn decode_into() {
+    let conf = Config {
+        bdr_xl: BdrGen2::Hz120.into(),
+        bdr_gy: BdrGen2::Hz120.into(),
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    };
+    let samples: Vec<_> = (0..16u32)
+        .flat_map(|i| [SensorType::Accelerometer, SensorType::Gyroscope].map(|sensor_tag| slot(sensor_tag, i * DTIME_120HZ)))
+        .collect();
+
+    let mut encoder = FifoEncoder::new(&conf, true, TsDecimation::X8).unwrap();
+    let mut raw = [RawSlot::default(); 64];
+    let n = encoder.encode(&samples, &mut raw).unwrap();
+
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut demux = Demux::<32>::new(&[SensorType::Accelerometer, SensorType::Gyroscope], OverflowPolicy::DropOldest);
+    assert_eq!(fifo.decode_into(&mut demux, &raw, n, false), Status::Ok);
+
+    assert_eq!(demux.len(SensorType::Gyroscope), 16);
+    assert_eq!(demux.stats(SensorType::Accelerometer), DemuxStats { high_water: 16, dropped: 0 });
+
+    let mut xl: Vec<_> = demux.drain(SensorType::Accelerometer).collect();
+    xl.sort_by_key(|s| s.timestamp);
+    assert_eq!(xl, samples.iter().filter(|s| s.sensor_tag == SensorType::Accelerometer).copied().collect::<Vec<_>>());
+    assert!(demux.is_empty(SensorType::Accelerometer));
+    assert_eq!(demux.len(SensorType::Gyroscope), 16);
+}
+
+#[test]
+fn overflow_policies() {
+    let times = |demux: &mut Demux<4>| demux.drain(SensorType::Accelerometer).map(|s| s.timestamp).collect::<Vec<_>>();
+
+    let mut demux = Demux::<4>::new(&[SensorType::Accelerometer], OverflowPolicy::DropOldest);
+    demux.extend(&(0..6).map(|t| slot(SensorType::Accelerometer, t)).collect::<Vec<_>>());
+    assert_eq!(demux.stats(SensorType::Accelerometer), DemuxStats { high_water: 4, dropped: 2 });
+    assert_eq!(times(&mut demux), [2, 3, 4, 5]);
+
+    let mut demux = Demux::<4>::new(&[SensorType::Accelerometer], OverflowPolicy::DropNewest);
+    for t in 0..6 {
+        assert_eq!(demux.push(slot(SensorType::Accelerometer, t)), t < 4);
+    }
+    assert_eq!(demux.peek(SensorType::Accelerometer).map(|s| s.timestamp), Some(0));
+    assert_eq!(times(&mut demux), [0, 1, 2, 3]);
+
+    demux.reset_stats();
+    assert_eq!(demux.stats(SensorType::Accelerometer), DemuxStats::default());
+}
+
+#[test]
+fn disabled_sensors() {
+    let mut demux = Demux::<4>::new(&[SensorType::Accelerometer], OverflowPolicy::DropOldest);
+
+    assert!(!demux.push(slot(SensorType::Gyroscope, 0)));
+    assert!(!demux.is_enabled(SensorType::Gyroscope));
+    assert_eq!(demux.len(SensorType::Gyroscope), 0);
+    assert_eq!(demux.pop(SensorType::Gyroscope), None);
+}
+
+#[test]
+fn size() {
+    // one ring per sensor type: about 28 * N slots of 12 bytes
+    let size = core::mem::size_of::<Demux<256>>();
+    assert!((28 * 256 * 12..29 * 256 * 12).contains(&size), "{size}");
+}
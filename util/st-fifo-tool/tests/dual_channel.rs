@@ -0,0 +1,117 @@
+use st_fifo_tool::*;
+
+const T0: u32 = 1_000_000;
+
+fn conf() -> Config {
+    Config {
+        bdr_xl: BdrGen2::Hz960.into(),
+        bdr_dualc: BdrGen2::Hz120.into(),
+        fs_dualc: XlFullScale::G16,
+        bdr_eis: BdrGen2::Hz240.into(),
+        fs_eis: GyFullScale::Dps4000,
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    }
+}
+
+fn slot(sensor_tag: SensorType, timestamp: u32, data: [i16; 3]) -> OutSlot {
+    OutSlot { timestamp, sensor_tag, sensor_data: SensorData { data } }
+}
+
+#[test]
+fn independent_timestamps() {
+    let conf = conf();
+
+    // words land in the first 960 Hz time slot after the sample, sometimes one slot late
+    let eis_times = [0, 192, 432, 576, 768];
+    let dualc_times = [0, 432, 768];
+    let mut samples = Vec::new();
+
+    for i in 0..17u32 {
+        let t = T0 + i * 48;
+        samples.push(slot(SensorType::Accelerometer, t, [0; 3]));
+
+        if eis_times.contains(&(i * 48)) {
+            samples.push(slot(SensorType::EisGyro, t, [1, 2, 3]));
+        }
+        if dualc_times.contains(&(i * 48)) {
+            samples.push(slot(SensorType::DualAccel, t, [4, 5, 6]));
+        }
+    }
+
+    let mut encoder = FifoEncoder::new(&conf, false, TsDecimation::Off).unwrap();
+    let mut raw = [RawSlot::default(); 64];
+    let n = encoder.encode(&samples, &mut raw).unwrap();
+
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut out = [OutSlot::default(); 64];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, n), Status::Ok);
+
+    let times = |sensor_tag: SensorType| -> Vec<u32> {
+        out[..out_size as usize]
+            .iter()
+            .filter(|s| s.sensor_tag == sensor_tag)
+            .map(|s| s.timestamp - T0)
+            .collect()
+    };
+
+    assert_eq!(times(SensorType::EisGyro), [0, 192, 384, 576, 768]);
+    assert_eq!(times(SensorType::DualAccel), [0, 384, 768]);
+    assert_eq!(times(SensorType::Accelerometer), (0..17).map(|i| i * 48).collect::<Vec<_>>());
+}
+
+#[test]
+fn full_scales() {
+    let conf = conf();
+
+    // 16 g: 0.488 mg/LSB, 4000 dps: 140 mdps/LSB
+    let Measurement::Acceleration(a) = slot(SensorType::DualAccel, 0, [1000, 0, 0]).to_si(&conf) else {
+        panic!("not an acceleration");
+    };
+    assert!((a.x - 0.488 * STANDARD_GRAVITY).abs() < 1e-3);
+
+    let Measurement::AngularRate(g) = slot(SensorType::EisGyro, 0, [100, 0, 0]).to_si(&conf) else {
+        panic!("not an angular rate");
+    };
+    assert!((g.x - 14.0).abs() < 1e-3);
+}
+
+#[test]
+fn unsupported_devices() {
+    // the high-g parts use the DualcXl code for the high-g accelerometer
+    let conf = Config { bdr_dualc: BdrGen2::Hz240.into(), ..Config::new(DeviceType::Lsm6dsv320x) };
+    assert!(FifoData::init(&conf).is_err());
+
+    let conf = Config { bdr_eis: BdrGen2::Hz480.into(), ..Config::new(DeviceType::Lsm6dsv320x) };
+    assert!(FifoData::init(&conf).is_ok());
+
+    let conf = Config { bdr_eis: BdrGen1::Hz416.into(), ..Config::new(DeviceType::Lsm6dso) };
+    assert!(FifoData::init(&conf).is_err());
+
+    let conf = Config { fs_eis: GyFullScale::Dps4000, ..Config::new(DeviceType::Lsm6dso) };
+    assert!(FifoData::init(&conf).is_err());
+}
+
+#[test]
+fn high_g_code() {
+    let conf = Config {
+        bdr_hg: BdrGen2::Hz480.into(),
+        fs_hg: Some(HgFullScale::G320),
+        ..Config::new(DeviceType::Lsm6dsv320x)
+    };
+    assert_eq!(Tag::XlHg.to_raw(), 0x1D);
+    assert_eq!(Tag::DualcXl.to_raw(), 0x1D);
+    assert_eq!(Tag::XlHg as u8 & 0x1F, 0x1D);
+    assert_eq!(Tag::from_raw(0x1D, 2), Ok(Tag::XlHg));
+    assert_eq!(Tag::from_raw(0x1D, 1), Ok(Tag::DualcXl));
+
+    let raw = [RawSlot { fifo_data_out: [0x1D << 3, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06] }];
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut out = [OutSlot::default(); MAX_SLOTS_PER_WORD];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, 1), Status::Ok);
+
+    assert_eq!(out_size, 1);
+    assert_eq!(out[0].sensor_tag, SensorType::HighGAccel);
+    assert_eq!(out[0].sensor_data.data, [0x0201, 0x0403, 0x0605]);
+}
@@ -0,0 +1,217 @@
+use proptest::prelude::*;
+use st_fifo_tool::*;
+
+const T0: u32 = 1_000_000;
+
+struct Stream {
+    samples: Vec<OutSlot>,
+}
+
+impl Stream {
+    /// Builds the samples batched in `slots` time slots starting at `t0`; axis data
+    /// follows a random walk with the given steps.
+    fn new(conf: &Config, t0: u32, slots: u32, steps: &[i16]) -> Self {
+        let device = &DEVICES[conf.device.fifo_ver() as usize];
+        let dtime = |table: &[f32; 16], bdr: Bdr| device.dtime[table.iter().position(|&b| b == bdr.hz()).unwrap()];
+        let dtime_xl = dtime(&device.bdr_acc, conf.bdr_xl);
+        let dtime_gy = dtime(&device.bdr_gyr, conf.bdr_gy);
+        let dtime_min = [dtime_xl, dtime_gy].into_iter().filter(|&d| d != 0).min().unwrap();
+
+        let mut samples = Vec::new();
+        let mut data = [[0i16; 3]; 2];
+        let mut k = 0;
+
+        for slot in 0..slots {
+            let t = t0 + slot * dtime_min;
+
+            for (ch, (dtime, sensor)) in [(dtime_xl, SensorType::Accelerometer), (dtime_gy, SensorType::Gyroscope)]
+                .into_iter()
+                .enumerate()
+            {
+                if dtime == 0 || !(t - t0).is_multiple_of(dtime) {
+                    continue;
+                }
+
+                for value in data[ch].iter_mut() {
+                    *value = value.wrapping_add(steps[k % steps.len()]);
+                    k += 1;
+                }
+
+                samples.push(OutSlot {
+                    timestamp: t,
+                    sensor_tag: sensor,
+                    sensor_data: SensorData { data: data[ch] },
+                });
+            }
+
+            if slot % 5 == 0 {
+                samples.push(OutSlot {
+                    timestamp: t,
+                    sensor_tag: SensorType::Temperature,
+                    sensor_data: SensorData { data: [slot as i16, 0, 0] },
+                });
+            }
+        }
+
+        Stream { samples }
+    }
+}
+
+fn decode(conf: &Config, raw: &[RawSlot]) -> Vec<OutSlot> {
+    let mut fifo = FifoData::init(conf).unwrap();
+    let mut out = vec![OutSlot::default(); raw.len() * MAX_SLOTS_PER_WORD];
+    let mut out_size = 0;
+
+    assert_eq!(fifo.decode_with_overrun(&mut out, raw, &mut out_size, raw.len() as u16, false), Status::Ok);
+    out.truncate(out_size as usize);
+    out
+}
+
+fn key(slot: &OutSlot) -> (u32, u8, [i16; 3]) {
+    (slot.timestamp, slot.sensor_tag as u8, slot.sensor_data.data)
+}
+
+fn assert_round_trip(conf: &Config, samples: &[OutSlot], decoded: &[OutSlot]) {
+    let mut expected: Vec<_> = samples.iter().map(key).collect();
+    let mut actual: Vec<_> = decoded.iter().map(key).collect();
+    expected.sort();
+    actual.sort();
+
+    let missing: Vec<_> = expected.iter().filter(|k| !actual.contains(k)).collect();
+    let extra: Vec<_> = actual.iter().filter(|k| !expected.contains(k)).collect();
+
+    assert_eq!(expected.len(), actual.len(), "device {:?}", conf.device);
+    assert!(missing.is_empty() && extra.is_empty(), "device {:?}: missing {missing:?}, extra {extra:?}", conf.device);
+}
+
+fn bdr_pairs(device: DeviceType) -> Vec<(Bdr, Bdr)> {
+    let d = &DEVICES[device.fifo_ver() as usize];
+    let bdr = |table: &[f32; 16]| -> Vec<Bdr> {
+        table.iter().filter(|&&b| b > 10.0).map(|&b| Bdr::from_hz(device, b).unwrap()).collect()
+    };
+    let (xl, gy) = (bdr(&d.bdr_acc), bdr(&d.bdr_gyr));
+    let mut pairs = Vec::new();
+
+    for &x in &xl {
+        for &g in &gy {
+            pairs.push((x, g));
+        }
+        pairs.push((x, Bdr::Off));
+    }
+
+    pairs
+}
+
+fn step() -> impl Strategy<Value = i16> {
+    prop_oneof![-16i16..16, -128i16..128, any::<i16>()]
+}
+
+proptest! {
+    #[test]
+    fn round_trip(
+        device in prop::sample::select(vec![DeviceType::Lsm6dso, DeviceType::Lsm6dsv16x, DeviceType::Lsm6dsv320x]),
+        pair in any::<prop::sample::Index>(),
+        compression in any::<bool>(),
+        ts in prop::sample::select(vec![TsDecimation::Off, TsDecimation::X1, TsDecimation::X8, TsDecimation::X32]),
+        slots in 1u32..200,
+        steps in prop::collection::vec(step(), 1..64),
+    ) {
+        let pairs = bdr_pairs(device);
+        let (bdr_xl, bdr_gy) = pairs[pair.index(pairs.len())];
+        let conf = Config { bdr_xl, bdr_gy, ..Config::new(device) };
+        let stream = Stream::new(&conf, T0, slots, &steps);
+
+        let mut encoder = FifoEncoder::new(&conf, compression, ts).unwrap();
+        let mut raw = vec![RawSlot::default(); stream.samples.len() * 2 + 1];
+        let n = encoder.encode(&stream.samples, &mut raw).unwrap() as usize;
+
+        assert_round_trip(&conf, &stream.samples, &decode(&conf, &raw[..n]));
+    }
+}
+
+#[test]
+fn compression_paths() {
+    let conf = Config { bdr_xl: BdrGen2::Hz120.into(), ..Config::new(DeviceType::Lsm6dsv16x) };
+    let data = [
+        [0, 0, 0],
+        // 3x: t-2, t-1, t
+        [1, 2, 3],
+        [-15, 2, 3],
+        [0, 2, 3],
+        // 2x + NC
+        [100, 2, 3],
+        [-27, 2, 3],
+        [30000, 2, 3],
+        // NC_T_2 + NC_T_1 + NC
+        [-30000, 0, 0],
+        [30000, 0, 0],
+        [0, 0, 0],
+    ];
+    let samples: Vec<_> = data
+        .iter()
+        .enumerate()
+        .map(|(i, d)| OutSlot {
+            timestamp: T0 + i as u32 * 384,
+            sensor_tag: SensorType::Accelerometer,
+            sensor_data: SensorData { data: *d },
+        })
+        .collect();
+
+    let mut encoder = FifoEncoder::new(&conf, true, TsDecimation::Off).unwrap();
+    let mut raw = [RawSlot::default(); 16];
+    let n = encoder.encode(&samples, &mut raw).unwrap() as usize;
+    let tags: Vec<u8> = raw[..n].iter().map(|r| r.fifo_data_out[0] >> 3).collect();
+
+    assert_eq!(
+        tags,
+        [
+            Tag::Ts as u8,
+            Tag::Xl as u8,
+            Tag::XlCompressed3x as u8,
+            Tag::XlCompressed2x as u8,
+            Tag::Xl as u8,
+            Tag::XlUncompressedT2 as u8,
+            Tag::XlUncompressedT1 as u8,
+            Tag::Xl as u8,
+        ]
+    );
+    assert_round_trip(&conf, &samples, &decode(&conf, &raw[..n]));
+}
+
+#[test]
+fn bdr_change() {
+    let conf = Config {
+        bdr_xl: BdrGen1::Hz104.into(),
+        bdr_gy: BdrGen1::Hz104.into(),
+        ..Config::new(DeviceType::Lsm6dso)
+    };
+    let new_conf = Config {
+        bdr_xl: BdrGen1::Hz208.into(),
+        bdr_gy: BdrGen1::Hz52.into(),
+        ..Config::new(DeviceType::Lsm6dso)
+    };
+    let before = Stream::new(&conf, T0, 30, &[1, -2, 3]).samples;
+    let t_chg = T0 + 30 * 384;
+    let after = Stream::new(&new_conf, t_chg, 60, &[5, 7, -9]).samples;
+
+    let mut encoder = FifoEncoder::new(&conf, true, TsDecimation::X32).unwrap();
+    let mut raw = vec![RawSlot::default(); 256];
+    let mut n = encoder.encode(&before, &mut raw).unwrap() as usize;
+    n += encoder.change_bdr(t_chg, new_conf.bdr_xl, new_conf.bdr_gy, Bdr::Off, &mut raw[n..]).unwrap() as usize;
+    n += encoder.encode(&after, &mut raw[n..]).unwrap() as usize;
+
+    // every tag byte has odd parity on the first FIFO generation
+    assert!(raw[..n].iter().all(|r| r.fifo_data_out[0].count_ones() % 2 == 1));
+
+    let (changes, decoded): (Vec<_>, Vec<_>) =
+        decode(&conf, &raw[..n]).into_iter().partition(|s| s.sensor_tag == SensorType::BdrChange);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].timestamp, t_chg);
+    assert_eq!(
+        changes[0].sensor_data.to_bdr_change(),
+        BdrChangeData { xl: new_conf.bdr_xl, gy: new_conf.bdr_gy, vsens: Bdr::Off }
+    );
+
+    let samples: Vec<_> = before.into_iter().chain(after).collect();
+    assert_round_trip(&conf, &samples, &decoded);
+}
@@ -0,0 +1,81 @@
+use st_fifo_tool::*;
+
+const T0: u32 = 1_000_000;
+
+fn ext_slot(sensor_tag: SensorType, timestamp: u32, bytes: [u8; 6]) -> OutSlot {
+    let mut sensor_data = SensorData::default();
+    sensor_data.from_u8_arr(&bytes);
+
+    OutSlot { timestamp, sensor_tag, sensor_data }
+}
+
+#[test]
+fn lis2mdl() {
+    let conf = Config {
+        ext_sensors: [Some(ExtSensorSlot::new(ExtSensorFormat::LIS2MDL, BdrGen2::Hz60.into())), None, None, None],
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    };
+    // X = 1000, Y = -1000, Z = 0
+    let slot = ext_slot(SensorType::ExtSensor0, 0, [0xE8, 0x03, 0x18, 0xFC, 0x00, 0x00]);
+
+    assert_eq!(
+        slot.to_si(&conf),
+        Measurement::MagneticField(Vector3 { x: 150.0, y: -150.0, z: 0.0 })
+    );
+}
+
+#[test]
+fn lps22df() {
+    let conf = Config {
+        ext_sensors: [None, None, Some(ExtSensorSlot::new(ExtSensorFormat::LPS22DF, BdrGen2::Hz60.into())), None],
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    };
+    // 1013.25 hPa = 0x3F5400 LSB, 25.5 °C = 2550 LSB
+    let slot = ext_slot(SensorType::ExtSensor2, 0, [0x00, 0x54, 0x3F, 0xF6, 0x09, 0x00]);
+
+    assert_eq!(slot.to_si(&conf), Measurement::Pressure { pressure: 1013.25, temperature: 25.5 });
+
+    // the slot is not described: raw data only
+    let slot = ext_slot(SensorType::ExtSensor1, 0, [0; 6]);
+    assert_eq!(slot.to_si(&conf), Measurement::Unsupported);
+}
+
+#[test]
+fn timestamps_follow_slot_rate() {
+    let conf = Config {
+        bdr_xl: BdrGen2::Hz480.into(),
+        ext_sensors: [Some(ExtSensorSlot::new(ExtSensorFormat::LIS2MDL, BdrGen2::Hz60.into())), None, None, None],
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    };
+
+    // the sensor hub words land in the first 480 Hz time slot after the read,
+    // sometimes one slot late
+    let ext_times = [0, 864, 1536, 2400, 3072];
+    let mut samples = Vec::new();
+
+    for i in 0..33u32 {
+        let t = T0 + i * 96;
+        samples.push(OutSlot { timestamp: t, sensor_tag: SensorType::Accelerometer, ..OutSlot::default() });
+
+        if ext_times.contains(&(i * 96)) {
+            samples.push(ext_slot(SensorType::ExtSensor0, t, [0; 6]));
+        }
+    }
+
+    let mut encoder = FifoEncoder::new(&conf, false, TsDecimation::Off).unwrap();
+    let mut raw = [RawSlot::default(); 64];
+    let n = encoder.encode(&samples, &mut raw).unwrap();
+
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut out = [OutSlot::default(); 64];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, n), Status::Ok);
+
+    let ext: Vec<u32> = out[..out_size as usize]
+        .iter()
+        .filter(|s| s.sensor_tag == SensorType::ExtSensor0)
+        .map(|s| s.timestamp - T0)
+        .collect();
+
+    assert_eq!(ext, [0, 768, 1536, 2304, 3072]);
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "ffi")]
+
+use st_fifo_tool::ffi::*;
+use st_fifo_tool::*;
+
+const ST_FIFO_OK: i32 = 0;
+const ST_FIFO_ERR: i32 = 1;
+
+fn encode(conf: &Config, compression: bool) -> Vec<RawSlot> {
+    let samples: Vec<_> = (0..8u32)
+        .flat_map(|i| {
+            [SensorType::Accelerometer, SensorType::Gyroscope].map(|sensor_tag| OutSlot {
+                timestamp: 1000 + i * 384,
+                sensor_tag,
+                sensor_data: SensorData { data: [i as i16, 2, 3] },
+            })
+        })
+        .collect();
+
+    let mut encoder = FifoEncoder::new(conf, compression, TsDecimation::X8).unwrap();
+    let mut raw = vec![RawSlot::default(); samples.len() * 2];
+    let n = encoder.encode(&samples, &mut raw).unwrap() as usize;
+    raw.truncate(n);
+    raw
+}
+
+// a single test: the C API has a global decoder
+#[test]
+fn c_api() {
+    let conf = StFifoConf {
+        device: DeviceType::Lsm6dsv16x as i32,
+        bdr_xl: 120.0,
+        bdr_gy: 120.0,
+        bdr_vsens: 0.0,
+    };
+    let config = Config {
+        bdr_xl: BdrGen2::Hz120.into(),
+        bdr_gy: BdrGen2::Hz120.into(),
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    };
+    let raw = encode(&config, true);
+
+    let mut out = vec![StFifoOutSlot::default(); raw.len() * 3];
+    let mut out_size = 0;
+
+    unsafe {
+        assert_eq!(st_fifo_set_report_events(1), ST_FIFO_ERR);
+        assert_eq!(st_fifo_init(&StFifoConf { bdr_xl: 100.0, ..conf }), ST_FIFO_ERR);
+        assert_eq!(st_fifo_init(&StFifoConf { device: 15, ..conf }), ST_FIFO_ERR);
+
+        assert_eq!(st_fifo_init(&conf), ST_FIFO_OK);
+        assert_eq!(st_fifo_decode(out.as_mut_ptr(), raw.as_ptr(), &mut out_size, raw.len() as u16), ST_FIFO_OK);
+        assert_eq!(out_size, 16);
+
+        st_fifo_sort(out.as_mut_ptr(), out_size);
+        assert!(out[..out_size as usize].windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+
+        let xl = SensorType::Accelerometer as i32;
+        assert_eq!(st_fifo_get_sensor_occurrence(out.as_ptr(), out_size, xl), 8);
+
+        let mut acc = vec![StFifoOutSlot::default(); 8];
+        st_fifo_extract_sensor(acc.as_mut_ptr(), out.as_ptr(), out_size, xl);
+
+        for (i, slot) in acc.iter().enumerate() {
+            assert_eq!(slot.sensor_tag, xl);
+            assert_eq!(slot.timestamp, 1000 + i as u32 * 384);
+            assert_eq!(slot.sensor_data, [i as i16, 2, 3]);
+        }
+
+        // the second time slot is lost (TS word, then one word per sensor and
+        // time slot): the DataLoss slots are only output on request
+        let mut lossy = encode(&config, false);
+        lossy.drain(3..5);
+        let data_loss = SensorType::DataLoss as i32;
+
+        for (report_events, losses) in [(0, 0), (1, 2)] {
+            let mut out = vec![StFifoOutSlot::default(); lossy.len() * MAX_SLOTS_PER_WORD];
+
+            assert_eq!(st_fifo_init(&conf), ST_FIFO_OK);
+            assert_eq!(st_fifo_set_report_events(report_events), ST_FIFO_OK);
+            assert_eq!(st_fifo_decode(out.as_mut_ptr(), lossy.as_ptr(), &mut out_size, lossy.len() as u16), ST_FIFO_OK);
+            assert_eq!(st_fifo_get_sensor_occurrence(out.as_ptr(), out_size, data_loss), losses);
+            assert_eq!(out_size, 14 + losses);
+        }
+
+        // st_fifo_init turns the events off
+        let mut out = vec![StFifoOutSlot::default(); lossy.len() * 3];
+        assert_eq!(st_fifo_init(&conf), ST_FIFO_OK);
+        assert_eq!(st_fifo_decode(out.as_mut_ptr(), lossy.as_ptr(), &mut out_size, lossy.len() as u16), ST_FIFO_OK);
+        assert_eq!(out_size, 14);
+    }
+}
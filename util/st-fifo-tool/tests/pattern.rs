@@ -0,0 +1,106 @@
+use st_fifo_tool::*;
+
+const DTIME_104HZ: u32 = 384;
+
+/// Gyroscope at 104 Hz and accelerometer at 52 Hz: 3 samples, 9 words per pattern.
+fn conf() -> Config {
+    Config {
+        pattern: PatternConfig {
+            odr_fifo: BdrGen1::Hz104.into(),
+            dec_gy: FifoDecimation::X1,
+            dec_xl: FifoDecimation::X2,
+            ..PatternConfig::new()
+        },
+        ..Config::new(DeviceType::Lsm6dsl)
+    }
+}
+
+/// FIFO_DATA_OUT words of `n` FIFO ODR periods: gyroscope [k, 1, 2], accelerometer [k, 3, 4].
+fn words(n: u16) -> Vec<u16> {
+    (0..n)
+        .flat_map(|k| {
+            let xl = (k % 2 == 0).then_some([k, 3, 4]);
+            [Some([k, 1, 2]), xl].into_iter().flatten().flatten()
+        })
+        .collect()
+}
+
+fn decode(fifo: &mut PatternFifo, words: &[u16], fifo_pattern: u16) -> Vec<OutSlot> {
+    let mut out = vec![OutSlot::default(); words.len() / 3 + 2];
+    let mut out_size = 0;
+
+    assert_eq!(fifo.decode(&mut out, words, &mut out_size, words.len() as u16, fifo_pattern), Status::Ok);
+    out.truncate(out_size as usize);
+    out
+}
+
+#[test]
+fn split_reads() {
+    let words = words(8);
+    let mut fifo = PatternFifo::init(&conf()).unwrap();
+    assert_eq!(fifo.pattern_words(), 9);
+
+    // the fourth sample is split across the two reads
+    let mut out = decode(&mut fifo, &words[..10], 0);
+    assert_eq!(out.len(), 3);
+    out.extend(decode(&mut fifo, &words[10..], 1));
+
+    let expected: Vec<_> = (0..8u32)
+        .flat_map(|k| {
+            let xl = (k % 2 == 0).then_some((SensorType::Accelerometer, [k as i16, 3, 4]));
+            [Some((SensorType::Gyroscope, [k as i16, 1, 2])), xl]
+                .into_iter()
+                .flatten()
+                .map(move |(sensor_tag, data)| OutSlot { timestamp: k * DTIME_104HZ, sensor_tag, sensor_data: SensorData { data } })
+        })
+        .collect();
+
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn overrun() {
+    let words = words(12);
+    let mut fifo = PatternFifo::init(&conf()).unwrap();
+    let first = decode(&mut fifo, &words[..12], 0);
+
+    // the words of 4 periods are lost: the read restarts at the beginning of a pattern
+    let out = decode(&mut fifo, &words[27..], 0);
+
+    assert_eq!(out[0].sensor_tag, SensorType::DataLoss);
+    assert_eq!(out[0].sensor_data.to_data_loss().cause, DataLossCause::Overrun);
+    assert_eq!((out[1].sensor_tag, out[1].sensor_data.data[0]), (SensorType::Gyroscope, 6));
+    assert!(out[1].timestamp > first.last().unwrap().timestamp);
+    assert!(out[1..].windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+}
+
+#[test]
+fn step_counter() {
+    let conf = Config {
+        pattern: PatternConfig {
+            dec_xl: FifoDecimation::Off,
+            dec_ds4: FifoDecimation::X4,
+            ds4: Ds4Content::StepCounter,
+            ..conf().pattern
+        },
+        ..conf()
+    };
+    let mut fifo = PatternFifo::init(&conf).unwrap();
+    assert_eq!(fifo.pattern_words(), 15);
+
+    // timestamp 0x123456, 42 steps
+    let words = [0, 1, 2, 0x1234, 0x5600, 42, 1, 1, 2];
+    let out = decode(&mut fifo, &words, 0);
+
+    assert_eq!(out[1].sensor_tag, SensorType::StepCounter);
+    assert_eq!(out[1].timestamp, 0x123456);
+    assert_eq!(out[1].payload(), Payload::StepCounter { steps: 42, timestamp: 0x123456 });
+    assert_eq!(out[2].timestamp, DTIME_104HZ);
+}
+
+#[test]
+fn tag_based_devices() {
+    assert!(FifoData::init(&conf()).is_err());
+    assert!(PatternFifo::init(&Config { pattern: conf().pattern, ..Config::new(DeviceType::Lsm6dso) }).is_err());
+    assert!(PatternFifo::init(&Config::new(DeviceType::Ism330dlc)).is_err());
+}
@@ -0,0 +1,130 @@
+use st_fifo_tool::*;
+
+fn raw_slot(tag: u8, payload: [u8; 6]) -> RawSlot {
+    let mut raw = RawSlot::default();
+    raw.fifo_data_out[0] = tag << 3;
+    raw.fifo_data_out[1..].copy_from_slice(&payload);
+    raw
+}
+
+#[test]
+fn axis_is_little_endian() {
+    let payload = Payload::from_tag(&Tag::Xl, &[0x01, 0x00, 0xFF, 0xFF, 0x00, 0x80]);
+
+    assert_eq!(payload, Payload::Axis(AxisData { x: 1, y: -1, z: -32768 }));
+}
+
+#[test]
+fn step_counter() {
+    let payload = Payload::from_tag(&Tag::StepCounter, &[0x2A, 0x00, 0x78, 0x56, 0x34, 0x12]);
+
+    assert_eq!(payload, Payload::StepCounter { steps: 42, timestamp: 0x1234_5678 });
+}
+
+#[test]
+fn mlc_result() {
+    let payload = Payload::from_tag(&Tag::MlcResult, &[0x05, 0x01, 0x10, 0x27, 0x00, 0x00]);
+
+    assert_eq!(payload, Payload::MlcResult { index: 1, value: 5, timestamp: 10000 });
+}
+
+#[test]
+fn mlc_filter_and_feature() {
+    let filter = Payload::from_tag(&Tag::MlcFilter, &[0x00, 0x3C, 0x02, 0x01, 0x00, 0x00]);
+    let feature = Payload::from_tag(&Tag::MlcFeature, &[0x00, 0xC0, 0x10, 0x00, 0x00, 0x00]);
+
+    assert_eq!(filter, Payload::MlcFilter { id: 0x0102, value: F16(0x3C00) });
+    assert_eq!(feature, Payload::MlcFeature { id: 0x10, value: F16(0xC000) });
+    assert_eq!(F16(0x3C00).to_f32(), 1.0);
+    assert_eq!(F16(0xC000).to_f32(), -2.0);
+}
+
+#[test]
+fn ext_sensor_nack() {
+    let payload = Payload::from_tag(&Tag::ExtSensNack, &[0x02, 0x00, 0x00, 0x00, 0x00, 0x00]);
+
+    assert_eq!(payload, Payload::ExtSensorNack { slave: 2 });
+}
+
+#[test]
+fn compressed_words_are_raw() {
+    let raw = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+
+    assert_eq!(Payload::from_tag(&Tag::XlCompressed2x, &raw), Payload::Raw(raw));
+}
+
+#[test]
+fn decoded_slots() {
+    let conf = Config::new(DeviceType::Lsm6dsv16x);
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let raw = [
+        raw_slot(Tag::MlcResult as u8, [0x03, 0x00, 0x40, 0xE2, 0x01, 0x00]),
+        raw_slot(Tag::StepCounter as u8, [0x0A, 0x01, 0xA0, 0x86, 0x01, 0x00]),
+    ];
+    let mut out = [OutSlot::default(); 2];
+    let mut out_size = 0;
+
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, 2), Status::Ok);
+    assert_eq!(out_size, 2);
+
+    assert_eq!(out[0].timestamp, 123456);
+    assert_eq!(out[0].payload(), Payload::MlcResult { index: 0, value: 3, timestamp: 123456 });
+    let mlc = out[0].sensor_data.to_mlc_result();
+    assert_eq!((mlc.mlc_res, mlc.mlc_idx), (3, 0));
+
+    assert_eq!(out[1].timestamp, 100000);
+    assert_eq!(out[1].payload(), Payload::StepCounter { steps: 266, timestamp: 100000 });
+}
+
+#[test]
+fn byte_helpers_are_little_endian() {
+    let bytes = [0x09, 0x40, 0xF7, 0xBF, 0x00, 0x80];
+    let mut data = [0; 3];
+    FifoData::bytes_to_i16_array(&bytes, &mut data);
+
+    let mut sensor_data = SensorData::default();
+    sensor_data.from_u8_arr(&bytes);
+
+    assert_eq!(data, [16393, -16393, -32768]);
+    assert_eq!(data, sensor_data.data);
+    assert_eq!(FifoData::combine_bytes_to_i16(0x09, 0x40), 0x4009);
+}
+
+/// FIFO_DATA_OUT_X_L..Z_H (79h..7Eh) of words built from the output formats of
+/// the LSM6DSV16X datasheet.
+#[test]
+fn datasheet_words() {
+    // accelerometer at ±2 g (0.061 mg/LSB): +1 g on Z is 16393 LSB
+    let payload = Payload::from_tag(&Tag::Xl, &[0x00, 0x00, 0x00, 0x00, 0x09, 0x40]);
+    assert_eq!(payload, Payload::Axis(AxisData { x: 0, y: 0, z: 16393 }));
+
+    // temperature: 0 LSB at 25 °C, 256 LSB/°C: 26 °C is 0x0100
+    let payload = Payload::from_tag(&Tag::Temp, &[0x00, 0x01, 0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(payload, Payload::Temperature(256));
+
+    // step counter: steps in X, timestamp in Y and Z
+    let payload = Payload::from_tag(&Tag::StepCounter, &[0xE8, 0x03, 0x00, 0x00, 0x01, 0x00]);
+    assert_eq!(payload, Payload::StepCounter { steps: 1000, timestamp: 0x0001_0000 });
+
+    // game rotation vector: X, Y, Z half-precision quaternion components
+    let payload = Payload::from_tag(&Tag::GameRv, &[0xA8, 0x39, 0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(payload, Payload::Quaternion { qx: F16(0x39A8), qy: F16(0), qz: F16(0) });
+
+    // MLC: result in X_L, MLC index in X_H, then the timestamp
+    let payload = Payload::from_tag(&Tag::MlcResult, &[0x04, 0x00, 0x00, 0x10, 0x00, 0x00]);
+    assert_eq!(payload, Payload::MlcResult { value: 4, index: 0, timestamp: 0x1000 });
+
+    // MLC filter and feature: half-precision value in X, identifier in Y
+    let payload = Payload::from_tag(&Tag::MlcFilter, &[0x00, 0x38, 0x03, 0x00, 0x00, 0x00]);
+    assert_eq!(payload, Payload::MlcFilter { id: 3, value: F16(0x3800) });
+    let payload = Payload::from_tag(&Tag::MlcFeature, &[0x00, 0x3C, 0x21, 0x00, 0x00, 0x00]);
+    assert_eq!(payload, Payload::MlcFeature { id: 0x21, value: F16(0x3C00) });
+
+    // sensor hub: the 6 bytes read from the slave
+    let raw = [0x28, 0x00, 0x80, 0x45, 0x09, 0x00];
+    assert_eq!(Payload::from_tag(&Tag::ExtSens0, &raw), Payload::ExtSensor(raw));
+
+    // sensor hub NACK: index of the slave in X_L
+    let payload = Payload::from_tag(&Tag::ExtSensNack, &[0x03, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(payload, Payload::ExtSensorNack { slave: 3 });
+}
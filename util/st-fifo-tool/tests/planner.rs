@@ -0,0 +1,65 @@
+use st_fifo_tool::planner::{Compression, Settings, plan};
+use st_fifo_tool::*;
+
+fn conf() -> Config {
+    Config {
+        bdr_xl: BdrGen2::Hz120.into(),
+        bdr_gy: BdrGen2::Hz120.into(),
+        ts_decimation: TsDecimation::X8,
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    }
+}
+
+fn settings(compression: Compression) -> Settings {
+    Settings {
+        compression,
+        fifo_depth: 512,
+        vsens_outputs: 0,
+        latency: 0.5,
+    }
+}
+
+#[test]
+fn uncompressed() {
+    let plan = plan(&conf(), &settings(Compression::Off)).unwrap();
+
+    // 240 samples/s + a TS word every 8 time slots of 120 Hz
+    assert_eq!(plan.samples_per_second, 240.0);
+    assert_eq!(plan.words_per_second, 255.0);
+    assert_eq!(plan.max_words_per_second, 255.0);
+    assert_eq!(plan.worst_case_words_per_sample, 255.0 / 240.0);
+    assert_eq!(plan.watermark, 127);
+    assert_eq!(plan.time_to_overrun, 512.0 / 255.0);
+    assert_eq!(plan.read_deadline, (512.0 - 127.0) / 255.0);
+}
+
+#[test]
+fn compressed() {
+    let plan = plan(&conf(), &settings(Compression::On { expected_ratio: 2.0 })).unwrap();
+
+    assert_eq!(plan.words_per_second, 135.0);
+    assert_eq!(plan.min_words_per_second, 95.0);
+    assert_eq!(plan.max_words_per_second, 255.0);
+    // the watermark holds the latency with the best compression
+    assert_eq!(plan.watermark, 47);
+}
+
+#[test]
+fn ts_decimation() {
+    // no TS word
+    let conf = Config { ts_decimation: TsDecimation::Off, ..conf() };
+    assert_eq!(plan(&conf, &settings(Compression::Off)).unwrap().words_per_second, 240.0);
+
+    // a TS word every time slot of 120 Hz
+    let conf = Config { ts_decimation: TsDecimation::X1, ..conf };
+    assert_eq!(plan(&conf, &settings(Compression::Off)).unwrap().words_per_second, 360.0);
+}
+
+#[test]
+fn invalid() {
+    let conf = Config { bdr_xl: BdrGen1::Hz104.into(), ..Config::new(DeviceType::Lsm6dsv16x) };
+    assert_eq!(plan(&conf, &settings(Compression::Off)), Err(Status::Err));
+
+    let conf = Config { bdr_xl: BdrGen2::Hz120.into(), ..Config::new(DeviceType::Lsm6dsv16x) };
+    assert_eq!(plan(&conf, &settings(Compression::On { expected_ratio: 4.0 })), Err(Status::Err));
+}
@@ -0,0 +1,168 @@
+use proptest::prelude::*;
+use st_fifo_tool::*;
+
+const T0: u32 = 1_000_000;
+
+fn device() -> impl Strategy<Value = DeviceType> {
+    (0u8..15).prop_map(|value| DeviceType::try_from(value).unwrap())
+}
+
+/// Any configuration, valid or not: BDR codes 0 to 15 of each channel.
+fn config() -> impl Strategy<Value = Config> {
+    (device(), prop::array::uniform8(0u8..16), any::<bool>()).prop_map(|(device, codes, ext)| {
+        let bdr = |code: u8| Bdr::from_code(device, code).unwrap_or(Bdr::Off);
+
+        Config {
+            bdr_xl: bdr(codes[0]),
+            bdr_gy: bdr(codes[1]),
+            bdr_vsens: bdr(codes[2]),
+            bdr_hg: bdr(codes[3]),
+            bdr_dualc: bdr(codes[4]),
+            bdr_eis: bdr(codes[5]),
+            ext_sensors: [ext.then(|| ExtSensorSlot::new(ExtSensorFormat::LIS2MDL, bdr(codes[6]))), None, None, None],
+            ts_decimation: TsDecimation::X8,
+            ..Config::new(device)
+        }
+    })
+}
+
+/// Arbitrary words, with the odd parity of the first FIFO generation if `parity`.
+fn raw_stream() -> impl Strategy<Value = Vec<RawSlot>> {
+    (prop::collection::vec(any::<[u8; 7]>(), 0..300), any::<bool>()).prop_map(|(words, parity)| {
+        words
+            .into_iter()
+            .map(|mut fifo_data_out| {
+                if parity && fifo_data_out[0].count_ones() % 2 == 0 {
+                    fifo_data_out[0] ^= 1;
+                }
+                RawSlot { fifo_data_out }
+            })
+            .collect()
+    })
+}
+
+proptest! {
+    #[test]
+    fn decode_never_panics(
+        conf in config(),
+        raw in raw_stream(),
+        out_len in 0usize..1000,
+        reads in prop::collection::vec((1usize..64, any::<bool>()), 1..8),
+    ) {
+        let Ok(mut fifo) = FifoData::init(&conf) else {
+            return Ok(());
+        };
+        let mut out = vec![OutSlot::default(); out_len];
+        let mut rest = &raw[..];
+
+        for (len, fifo_ovr) in reads {
+            let (words, next) = rest.split_at(len.min(rest.len()));
+            let mut out_size = 0;
+
+            if fifo.decode_with_overrun(&mut out, words, &mut out_size, words.len() as u16, fifo_ovr) == Status::Ok {
+                prop_assert!(out_size as usize <= out.len());
+            }
+
+            let mut reconstructed = fifo.clone();
+            reconstructed.decode_reconstructed(&mut out, words, &mut out_size, words.len() as u16, fifo_ovr, Some(0));
+            reconstructed.ts_error_bound();
+            reconstructed.host_time_ns(u32::MAX);
+
+            let mut demux = Demux::<8>::new(&[SensorType::Accelerometer, SensorType::DataLoss], OverflowPolicy::DropOldest);
+            fifo.clone().decode_into(&mut demux, words, words.len() as u16, fifo_ovr);
+
+            rest = next;
+        }
+    }
+
+    #[test]
+    fn pattern_decode_never_panics(
+        odr in 0u8..16,
+        decimations in prop::array::uniform4(0u8..8),
+        words in prop::collection::vec(any::<u16>(), 0..300),
+        out_len in 0usize..200,
+        reads in prop::collection::vec((1usize..64, any::<u16>()), 1..8),
+    ) {
+        let decimation = |code: u8| [
+            FifoDecimation::Off, FifoDecimation::X1, FifoDecimation::X2, FifoDecimation::X3,
+            FifoDecimation::X4, FifoDecimation::X8, FifoDecimation::X16, FifoDecimation::X32,
+        ][code as usize];
+        let conf = Config {
+            pattern: PatternConfig {
+                odr_fifo: Bdr::from_code(DeviceType::Lsm6dsl, odr).unwrap_or(Bdr::Off),
+                dec_gy: decimation(decimations[0]),
+                dec_xl: decimation(decimations[1]),
+                dec_ds3: decimation(decimations[2]),
+                dec_ds4: decimation(decimations[3]),
+                ds4: Ds4Content::StepCounter,
+            },
+            ..Config::new(DeviceType::Lsm6dsl)
+        };
+        let Ok(mut fifo) = PatternFifo::init(&conf) else {
+            return Ok(());
+        };
+        let mut out = vec![OutSlot::default(); out_len];
+        let mut rest = &words[..];
+
+        for (len, fifo_pattern) in reads {
+            let (read, next) = rest.split_at(len.min(rest.len()));
+            let mut out_size = 0;
+            fifo.decode(&mut out, read, &mut out_size, read.len() as u16, fifo_pattern % (fifo.pattern_words() + 1));
+            rest = next;
+        }
+    }
+
+    #[test]
+    fn monotonic_timestamps(
+        device in prop::sample::select(vec![DeviceType::Lsm6dso, DeviceType::Lsm6dsv16x, DeviceType::Lsm6dsv320x]),
+        bdr in (1u8..8, 0u8..8),
+        compression in any::<bool>(),
+        ts in prop::sample::select(vec![TsDecimation::Off, TsDecimation::X1, TsDecimation::X8, TsDecimation::X32]),
+        slots in 1u32..300,
+        steps in prop::collection::vec(any::<i16>(), 1..16),
+        read_len in 1usize..40,
+    ) {
+        let code = |code: u8| Bdr::from_code(device, code).unwrap();
+        let conf = Config { bdr_xl: code(bdr.0 + 2), bdr_gy: code(bdr.1), ..Config::new(device) };
+        let dtime = |bdr: Bdr| bdr.dtime(device).unwrap();
+        let (dtime_xl, dtime_gy) = (dtime(conf.bdr_xl), dtime(conf.bdr_gy));
+        let dtime_min = if conf.bdr_gy.is_off() { dtime_xl } else { dtime_xl.min(dtime_gy) };
+
+        let mut samples = Vec::new();
+        let mut data = [0i16; 3];
+
+        for slot in 0..slots {
+            let t = slot * dtime_min;
+
+            for (sensor_tag, dtime) in [(SensorType::Accelerometer, dtime_xl), (SensorType::Gyroscope, dtime_gy)] {
+                if dtime != 0 && t.is_multiple_of(dtime) {
+                    data = data.map(|v| v.wrapping_add(steps[slot as usize % steps.len()]));
+                    samples.push(OutSlot { timestamp: T0 + t, sensor_tag, sensor_data: SensorData { data } });
+                }
+            }
+        }
+
+        let mut encoder = FifoEncoder::new(&conf, compression, ts).unwrap();
+        let mut raw = vec![RawSlot::default(); samples.len() * 2 + 1];
+        let n = encoder.encode(&samples, &mut raw).unwrap() as usize;
+
+        let mut fifo = FifoData::init(&conf).unwrap();
+        let mut last = [None; 2];
+
+        for words in raw[..n].chunks(read_len) {
+            let mut out = vec![OutSlot::default(); words.len() * 3];
+            let mut out_size = 0;
+            prop_assert_eq!(fifo.decode(&mut out, words, &mut out_size, words.len() as u16), Status::Ok);
+
+            for slot in &out[..out_size as usize] {
+                let k = match slot.sensor_tag {
+                    SensorType::Accelerometer => 0,
+                    SensorType::Gyroscope => 1,
+                    sensor_tag => panic!("unexpected {sensor_tag:?}"),
+                };
+                let previous = last[k].replace(slot.timestamp);
+                prop_assert!(previous.is_none_or(|t| t < slot.timestamp), "{:?} at {} after {:?}", slot.sensor_tag, slot.timestamp, previous);
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "serde")]
+
+use st_fifo_tool::*;
+
+fn samples() -> Vec<OutSlot> {
+    let conf = Config {
+        bdr_xl: BdrGen2::Hz120.into(),
+        bdr_gy: BdrGen2::Hz120.into(),
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    };
+    let samples: Vec<_> = (0..16u32)
+        .flat_map(|i| {
+            [SensorType::Accelerometer, SensorType::Gyroscope].map(|sensor_tag| OutSlot {
+                timestamp: 1000 + i * 384,
+                sensor_tag,
+                sensor_data: SensorData { data: [i as i16, -(i as i16), 7] },
+            })
+        })
+        .collect();
+
+    let mut encoder = FifoEncoder::new(&conf, true, TsDecimation::X8).unwrap();
+    let mut raw = vec![RawSlot::default(); samples.len() * 2];
+    let n = encoder.encode(&samples, &mut raw).unwrap();
+
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut out = vec![OutSlot::default(); samples.len() + 1];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, n), Status::Ok);
+    fifo.sort(&mut out, out_size);
+    out.truncate(out_size as usize);
+
+    assert_eq!(out, samples);
+    out
+}
+
+#[test]
+fn out_slots() {
+    let out = samples();
+    let frame = postcard::to_allocvec(&out).unwrap();
+
+    assert_eq!(postcard::from_bytes::<Vec<OutSlot>>(&frame).unwrap(), out);
+}
+
+#[test]
+fn config_and_measurements() {
+    let mut conf = Config {
+        bdr_hg: BdrGen2::Hz960.into(),
+        ext_sensors: [Some(ExtSensorSlot::new(ExtSensorFormat::LPS22DF, BdrGen2::Hz60.into())), None, None, None],
+        ..Config::new(DeviceType::Lsm6dsv320x)
+    };
+    conf.custom_tags.register(0x1F).unwrap();
+    let frame = postcard::to_allocvec(&conf).unwrap();
+    assert_eq!(postcard::from_bytes::<Config>(&frame).unwrap(), conf);
+
+    let ctx = TagContext {
+        tag: 0x1F,
+        sensor_type: SensorType::Custom0,
+        tag_counter: 2,
+        fifo_ver: 2,
+        timestamp: 768,
+        dtime_min: 384,
+    };
+    let frame = postcard::to_allocvec(&ctx).unwrap();
+    assert_eq!(postcard::from_bytes::<TagContext>(&frame).unwrap(), ctx);
+
+    let measurement = Measurement::Pressure { pressure: 1013.25, temperature: 21.5 };
+    let frame = postcard::to_allocvec(&measurement).unwrap();
+    assert_eq!(postcard::from_bytes::<Measurement>(&frame).unwrap(), measurement);
+
+    let loss = DataLossData {
+        cause: DataLossCause::TagCounter,
+        sensor_type: SensorType::Gyroscope,
+        lost_samples: 3,
+    };
+    let frame = postcard::to_allocvec(&Payload::DataLoss(loss)).unwrap();
+    assert_eq!(postcard::from_bytes::<Payload>(&frame).unwrap(), Payload::DataLoss(loss));
+}
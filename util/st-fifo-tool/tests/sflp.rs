@@ -0,0 +1,132 @@
+use st_fifo_tool::*;
+
+const HALF_SQRT2: u16 = 0x39A8; // 0.70703125, closest half-precision value to sin(45°)
+const ONE: u16 = 0x3C00;
+
+fn assert_close(a: f32, b: f32, eps: f32) {
+    assert!((a - b).abs() <= eps, "{a} != {b}");
+}
+
+fn assert_quaternion(q: Quaternion, [w, x, y, z]: [f32; 4]) {
+    for (a, b) in [(q.w, w), (q.x, x), (q.y, y), (q.z, z)] {
+        assert_close(a, b, 1e-3);
+    }
+    assert_close(q.norm(), 1.0, 1e-6);
+}
+
+#[test]
+fn half_precision() {
+    for (half, value) in [
+        (0x0000, 0.0),
+        (ONE, 1.0),
+        (0xC000, -2.0),
+        (0x3555, 0.333_251_95),
+        (0x7BFF, 65504.0),
+        (0x0001, 1.0 / 16_777_216.0), // smallest subnormal
+        (0x0200, 1.0 / 32_768.0),
+        (0x7C00, f32::INFINITY),
+        (0xFC00, f32::NEG_INFINITY),
+    ] {
+        assert_eq!(F16(half).to_f32(), value, "{half:#06X}");
+    }
+
+    assert!(F16(0x8000).to_f32().is_sign_negative());
+    assert!(F16(0x7E00).to_f32().is_nan());
+}
+
+#[test]
+fn identity() {
+    let q = Quaternion::from_sflp(0, 0, 0);
+
+    assert_eq!(q, Quaternion::default());
+    assert_eq!(q.to_euler(), EulerAngles::default());
+}
+
+#[test]
+fn quarter_turns() {
+    let s = core::f32::consts::FRAC_1_SQRT_2;
+
+    let q = Quaternion::from_sflp(HALF_SQRT2, 0, 0);
+    assert_quaternion(q, [s, s, 0.0, 0.0]);
+    let euler = q.to_euler();
+    assert_close(euler.roll, 90.0, 0.1);
+    assert_close(euler.pitch, 0.0, 0.1);
+    assert_close(euler.yaw, 0.0, 0.1);
+
+    let q = Quaternion::from_sflp(0, HALF_SQRT2, 0);
+    assert_quaternion(q, [s, 0.0, s, 0.0]);
+    assert_close(q.to_euler().pitch, 90.0, 0.1);
+
+    let q = Quaternion::from_sflp(0, 0, HALF_SQRT2 | 0x8000);
+    assert_quaternion(q, [s, 0.0, 0.0, -s]);
+    let euler = q.to_euler();
+    assert_close(euler.roll, 0.0, 0.1);
+    assert_close(euler.pitch, 0.0, 0.1);
+    assert_close(euler.yaw, -90.0, 0.1);
+}
+
+#[test]
+fn scalar_part_near_zero() {
+    // half turn about X
+    let q = Quaternion::from_sflp(ONE, 0, 0);
+    assert_eq!(q, Quaternion { w: 0.0, x: 1.0, y: 0.0, z: 0.0 });
+    assert_close(q.to_euler().roll.abs(), 180.0, 1e-3);
+
+    // the rounded vector part is longer than one: normalized, w = 0
+    let q = Quaternion::from_sflp(HALF_SQRT2 + 1, HALF_SQRT2 + 1, 0);
+    assert_eq!(q.w, 0.0);
+    assert_quaternion(q, [0.0, core::f32::consts::FRAC_1_SQRT_2, core::f32::consts::FRAC_1_SQRT_2, 0.0]);
+
+    // the vector part is slightly shorter than one
+    let q = Quaternion::from_sflp(0x3BFF, 0, 0); // 0.99951172
+    assert_close(q.w, (1.0f32 - 0.999_511_7 * 0.999_511_7).sqrt(), 1e-4);
+    assert!(q.w > 0.0);
+}
+
+#[test]
+fn game_rotation_vector_word() {
+    // the half-precision components are little-endian in the word
+    let conf = Config { bdr_vsens: BdrGen2::Hz30.into(), ..Config::new(DeviceType::Lsm6dsv16x) };
+    let [lo, hi] = HALF_SQRT2.to_le_bytes();
+    let raw = [RawSlot { fifo_data_out: [(Tag::GameRv as u8) << 3, lo, hi, 0, 0, 0, 0] }];
+
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut out = [OutSlot::default(); MAX_SLOTS_PER_WORD];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, 1), Status::Ok);
+    assert_eq!(out[0].sensor_tag, SensorType::GameRv6x);
+
+    let s = core::f32::consts::FRAC_1_SQRT_2;
+    assert_quaternion(out[0].sensor_data.to_sflp_quaternion(), [s, s, 0.0, 0.0]);
+
+    let Measurement::Quaternion(q) = out[0].to_si(&conf) else {
+        panic!("{:?}", out[0].to_si(&conf));
+    };
+    assert_quaternion(q, [s, s, 0.0, 0.0]);
+}
+
+#[test]
+fn gravity_and_gyro_bias() {
+    let conf = Config::new(DeviceType::Lsm6dsv16x);
+
+    // 0.061 mg/LSB: 16393 LSB is 1 g
+    let gravity = SensorData { data: [0, -16393, 16393] };
+    let g = gravity.to_gravity();
+    assert_eq!(g.x, 0.0);
+    assert_close(g.y, -999.973, 1e-3);
+    assert_close(g.z, 999.973, 1e-3);
+
+    let slot = OutSlot { timestamp: 0, sensor_tag: SensorType::Gravity, sensor_data: gravity };
+    let Measurement::Gravity(g) = slot.to_si(&conf) else {
+        panic!("{:?}", slot.to_si(&conf));
+    };
+    assert_close(g.z, 0.999_973 * STANDARD_GRAVITY, 1e-4);
+
+    // 4.375 mdps/LSB
+    let bias = SensorData { data: [1000, -8, 0] };
+    let b = bias.to_gyro_bias();
+    assert_eq!((b.x, b.y, b.z), (4375.0, -35.0, 0.0));
+
+    let slot = OutSlot { timestamp: 0, sensor_tag: SensorType::GyroBias, sensor_data: bias };
+    assert_eq!(slot.to_si(&conf), Measurement::GyroBias(Vector3 { x: 4.375, y: -0.035, z: 0.0 }));
+}
@@ -0,0 +1,84 @@
+use st_fifo_tool::*;
+
+const DTIME_120HZ: u32 = 384;
+
+fn conf() -> Config {
+    Config {
+        bdr_xl: BdrGen2::Hz120.into(),
+        bdr_gy: BdrGen2::Hz120.into(),
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    }
+}
+
+/// Compressed stream of 40 accelerometer and gyroscope samples.
+fn encode() -> Vec<RawSlot> {
+    let samples: Vec<_> = (0..40u32)
+        .flat_map(|i| {
+            [SensorType::Accelerometer, SensorType::Gyroscope].map(|sensor_tag| OutSlot {
+                timestamp: 1000 + i * DTIME_120HZ,
+                sensor_tag,
+                sensor_data: SensorData { data: [i as i16 * 3, -(i as i16), 100] },
+            })
+        })
+        .collect();
+
+    let mut encoder = FifoEncoder::new(&conf(), true, TsDecimation::X32).unwrap();
+    let mut raw = vec![RawSlot::default(); samples.len() * 2];
+    let n = encoder.encode(&samples, &mut raw).unwrap() as usize;
+    raw.truncate(n);
+    raw
+}
+
+fn decode(fifo: &mut FifoData, raw: &[RawSlot]) -> Vec<OutSlot> {
+    let mut out = vec![OutSlot::default(); raw.len() * 3];
+    let mut out_size = 0;
+
+    assert_eq!(fifo.decode(&mut out, raw, &mut out_size, raw.len() as u16), Status::Ok);
+    out.truncate(out_size as usize);
+    out
+}
+
+#[test]
+fn resume_after_sleep() {
+    let raw = encode();
+    let expected = decode(&mut FifoData::init(&conf()).unwrap(), &raw);
+
+    for split in [1, raw.len() / 3, raw.len() / 2] {
+        let mut fifo = FifoData::init(&conf()).unwrap();
+        let mut out = decode(&mut fifo, &raw[..split]);
+        let retained = fifo.snapshot();
+
+        // wake-up: the decoder is rebuilt from the configuration and the retained state
+        let mut fifo = FifoData::init(&conf()).unwrap();
+        fifo.restore(&retained).unwrap();
+        out.extend(decode(&mut fifo, &raw[split..]));
+
+        assert_eq!(out, expected, "split at {split}");
+        assert_eq!(fifo.snapshot(), {
+            let mut all = FifoData::init(&conf()).unwrap();
+            decode(&mut all, &raw);
+            all.snapshot()
+        });
+    }
+}
+
+#[test]
+fn invalid_snapshots() {
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    decode(&mut fifo, &encode()[..10]);
+    let snapshot = fifo.snapshot();
+
+    // another FIFO generation
+    let mut other = FifoData::init(&Config { bdr_xl: BdrGen1::Hz104.into(), ..Config::new(DeviceType::Lsm6dso) }).unwrap();
+    assert_eq!(other.restore(&snapshot), Err(Status::Err));
+
+    // layout version 1 only
+    assert_eq!(snapshot[0], 1);
+    let mut version = snapshot;
+    version[0] = 2;
+    assert_eq!(fifo.restore(&version), Err(Status::Err));
+    assert_eq!(fifo.restore(&snapshot[..SNAPSHOT_SIZE - 1]), Err(Status::Err));
+
+    // a failed restore leaves the state unchanged
+    assert_eq!(fifo.snapshot(), snapshot);
+}
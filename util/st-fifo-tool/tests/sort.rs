@@ -0,0 +1,59 @@
+use proptest::prelude::*;
+use st_fifo_tool::*;
+
+fn slots(timestamps: &[u32]) -> Vec<OutSlot> {
+    timestamps
+        .iter()
+        .enumerate()
+        .map(|(i, &timestamp)| OutSlot {
+            timestamp,
+            sensor_tag: SensorType::Accelerometer,
+            // the position is kept to check the stability
+            sensor_data: SensorData { data: [i as i16, 0, 0] },
+        })
+        .collect()
+}
+
+fn key(slot: &OutSlot) -> (u32, i16) {
+    (slot.timestamp, slot.sensor_data.data[0])
+}
+
+proptest! {
+    #[test]
+    fn sort_is_stable(timestamps in prop::collection::vec(0u32..64, 0..1600)) {
+        let fifo = FifoData::init(&Config::new(DeviceType::Lsm6dsv16x)).unwrap();
+        let mut out = slots(&timestamps);
+        let mut expected = out.clone();
+
+        let len = out.len() as u16;
+
+        fifo.sort(&mut out, len);
+        expected.sort_by_key(|slot| slot.timestamp);
+
+        prop_assert_eq!(out.iter().map(key).collect::<Vec<_>>(), expected.iter().map(key).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn merge_runs(mut runs in prop::collection::vec(prop::collection::vec(0u32..1000, 0..100), 1..6)) {
+        let fifo = FifoData::init(&Config::new(DeviceType::Lsm6dsv16x)).unwrap();
+        let runs: Vec<Vec<OutSlot>> = runs
+            .iter_mut()
+            .map(|run| {
+                run.sort();
+                slots(run)
+            })
+            .collect();
+        let mut expected: Vec<OutSlot> = runs.iter().flatten().copied().collect();
+        expected.sort_by_key(|slot| slot.timestamp);
+
+        // merge in two steps, with a small output buffer first
+        let mut heads: Vec<&[OutSlot]> = runs.iter().map(|run| run.as_slice()).collect();
+        let mut out = vec![OutSlot::default(); expected.len()];
+        let n = fifo.merge(&mut heads, &mut out[..expected.len() / 2]) as usize;
+        let n = n + fifo.merge(&mut heads, &mut out[n..]) as usize;
+
+        prop_assert_eq!(n, expected.len());
+        prop_assert!(heads.iter().all(|run| run.is_empty()));
+        prop_assert_eq!(out.iter().map(|s| s.timestamp).collect::<Vec<_>>(), expected.iter().map(|s| s.timestamp).collect::<Vec<_>>());
+    }
+}
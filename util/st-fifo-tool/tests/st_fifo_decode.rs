@@ -0,0 +1,124 @@
+#![cfg(feature = "std")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/lsm6dsv16x.hex");
+const CONF: [&str; 6] = ["--device", "lsm6dsv16x", "--bdr-xl", "120", "--bdr-gy", "120"];
+
+/// Words of the fixture: TS, accelerometer and gyroscope at counters 0, 1 and 3.
+const WORDS: [[u8; 7]; 7] = [
+    [0x20, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00],
+    [0x10, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00],
+    [0x08, 0x04, 0x00, 0x05, 0x00, 0x06, 0x00],
+    [0x12, 0x07, 0x00, 0x08, 0x00, 0x09, 0x00],
+    [0x0A, 0x0A, 0x00, 0x0B, 0x00, 0x0C, 0x00],
+    [0x16, 0x0D, 0x00, 0x0E, 0x00, 0x0F, 0x00],
+    [0x0E, 0x10, 0x00, 0x11, 0x00, 0x12, 0x00],
+];
+
+fn run(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_st-fifo-decode"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn hex_to_csv() {
+    let output = run(&[&CONF[..], &[FIXTURE]].concat(), &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let lines = stdout(&output);
+    let columns = |line: &str| line.split(',').take(6).collect::<Vec<_>>().join(",");
+
+    assert_eq!(lines[0], "ticks,time_ns,sensor,raw_x,raw_y,raw_z,x,y,z,w,unit");
+    assert_eq!(
+        lines[1..].iter().map(|line| columns(line)).collect::<Vec<_>>(),
+        [
+            "256,5568000,Accelerometer,1,2,3",
+            "256,5568000,Gyroscope,4,5,6",
+            "640,13920000,Accelerometer,7,8,9",
+            "640,13920000,Gyroscope,10,11,12",
+            "1024,22272000,DataLoss,1,1,1",
+            "1024,22272000,DataLoss,1,0,1",
+            "1408,30624000,Accelerometer,13,14,15",
+            "1408,30624000,Gyroscope,16,17,18",
+        ]
+    );
+    assert!(lines[1].ends_with(",,m/s^2"));
+    assert!(stderr(&output).contains("2 data losses"));
+}
+
+#[test]
+fn binary_to_jsonl() {
+    let output = run(&[&CONF[..], &["--output", "jsonl", "-"]].concat(), WORDS.as_flattened());
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let lines = stdout(&output);
+    assert_eq!(lines.len(), 8);
+    assert!(lines[0].starts_with(r#"{"ticks":256,"time_ns":5568000,"sensor":"Accelerometer","raw":[1,2,3],"value":{"x":"#));
+    assert!(lines[1].ends_with(r#""unit":"dps"}"#));
+    assert_eq!(lines[4], r#"{"ticks":1024,"time_ns":22272000,"sensor":"DataLoss","raw":[1,1,1]}"#);
+
+    // the hex text read from stdin gives the same output
+    let hex = std::fs::read(FIXTURE).unwrap();
+    let output_hex = run(&[&CONF[..], &["--output", "jsonl", "--input-format", "hex"]].concat(), &hex);
+    assert_eq!(stdout(&output_hex), lines);
+}
+
+#[test]
+fn invalid_words() {
+    let mut words = WORDS.to_vec();
+    words.insert(3, [0xF8, 0, 0, 0, 0, 0, 0]); // tag 0x1F
+
+    let output = run(&CONF, words.as_flattened());
+    assert!(output.status.success());
+    assert!(stderr(&output).contains("warning: word 3: invalid tag 0x1F"), "{}", stderr(&output));
+    assert!(stderr(&output).contains("1 invalid words"));
+    assert_eq!(stdout(&output).len(), 9);
+
+    // the tag byte of the first generation has odd parity
+    let output = run(&["--device", "lsm6dso", "--bdr-xl", "104"], &[0x11, 0, 0, 0, 0, 0, 0]);
+    assert!(stderr(&output).contains("warning: word 0: parity error in tag byte 0x11"), "{}", stderr(&output));
+}
+
+#[test]
+fn option_errors() {
+    for (args, msg) in [
+        (&["--bdr-xl", "120"][..], "--device is required"),
+        (&["--device", "lsm6dsx"], "unknown device 'lsm6dsx'"),
+        (&["--device", "lsm6dsv16x", "--bdr-xl", "100"], "100 Hz is not a batch data rate of Lsm6dsv16x"),
+        (&["--device", "lsm6dsv16x", "--bdr-xl"], "missing value for --bdr-xl"),
+        (&["--device", "lsm6dsv16x", "--fs-xl", "3g"], "invalid accelerometer full scale '3g'"),
+        (&["--device", "lsm6dso", "--fs-hg", "320g"], "G320 is not supported by Lsm6dso"),
+        (&["--device", "lsm6dsv16x", "--output", "xml"], "unknown output format 'xml'"),
+        (&["--device", "lsm6dsv16x", "--verbose"], "unknown option '--verbose'"),
+    ] {
+        let output = run(args, &[]);
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        assert!(stderr(&output).starts_with(&format!("error: {msg}\n")), "{args:?}: {}", stderr(&output));
+    }
+
+    // input errors
+    let output = run(&CONF, &[0; 8]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("input length (8 bytes) is not a multiple of 7"));
+
+    let output = run(&[&CONF[..], &["--input-format", "hex"]].concat(), b"10 0G");
+    assert!(stderr(&output).contains("line 1: invalid hex token '0G'"));
+}
@@ -0,0 +1,139 @@
+use st_fifo_tool::*;
+
+const T0: u32 = 1_000_000;
+
+fn conf() -> Config {
+    // 0.061 mg/LSB and 4.375 mdps/LSB
+    Config { bdr_xl: BdrGen2::Hz240.into(), bdr_gy: BdrGen2::Hz120.into(), ..Config::new(DeviceType::Lsm6dsv16x) }
+}
+
+fn slot(sensor_tag: SensorType, timestamp: u32, x: i16) -> OutSlot {
+    OutSlot { timestamp, sensor_tag, sensor_data: SensorData { data: [x, 0, 0] } }
+}
+
+/// Accelerometer at 240 Hz (x = 0, 10, 20, ...), gyroscope at 120 Hz (x = 0, 100, 200, ...).
+fn stream(n: u32) -> Vec<OutSlot> {
+    let mut slots = Vec::new();
+
+    for i in 0..n {
+        slots.push(slot(SensorType::Accelerometer, T0 + i * 192, 10 * i as i16));
+        if i % 2 == 0 {
+            slots.push(slot(SensorType::Gyroscope, T0 + i * 192, 50 * i as i16));
+        }
+    }
+
+    slots
+}
+
+fn run(sync: &mut Synchronizer, slots: &[OutSlot]) -> Vec<ImuSample> {
+    let mut out = Vec::new();
+
+    for slot in slots {
+        sync.push(slot);
+        while let Some(sample) = sync.pop() {
+            out.push(sample);
+        }
+    }
+
+    out
+}
+
+#[test]
+fn linear() {
+    let conf = conf();
+    // 240 Hz output: the gyroscope is interpolated between its samples
+    let mut sync = Synchronizer::new(&conf, 240.0, Interpolation::Linear, 192).unwrap();
+    let out = run(&mut sync, &stream(9));
+
+    assert_eq!(sync.period(), 192);
+    assert_eq!(out.len(), 9);
+
+    for (i, sample) in out.iter().enumerate() {
+        assert_eq!(sample.t, T0 + i as u32 * 192);
+        assert!((sample.accel.x - 10.0 * i as f32 * 0.061 * STANDARD_GRAVITY / 1000.0).abs() < 1e-4);
+        assert!((sample.gyro.x - 50.0 * i as f32 * 4.375 / 1000.0).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn zero_order_hold() {
+    let conf = conf();
+    let mut sync = Synchronizer::new(&conf, 240.0, Interpolation::ZeroOrderHold, 192).unwrap();
+    let out = run(&mut sync, &stream(9));
+
+    // the gyroscope holds its previous sample between two samples
+    assert_eq!(out.len(), 9);
+    assert_eq!(out[1].gyro, out[0].gyro);
+    assert_ne!(out[2].gyro, out[1].gyro);
+}
+
+#[test]
+fn max_skew() {
+    let conf = conf();
+    let mut slots = stream(9);
+    // gyroscope samples missing between T0 and T0 + 6 * 192
+    slots.retain(|s| s.sensor_tag != SensorType::Gyroscope || s.timestamp == T0 || s.timestamp >= T0 + 6 * 192);
+
+    let mut sync = Synchronizer::new(&conf, 240.0, Interpolation::Linear, 192).unwrap();
+    let out = run(&mut sync, &slots);
+
+    assert_eq!(out.iter().map(|s| (s.t - T0) / 192).collect::<Vec<_>>(), [0, 1, 5, 6, 7, 8]);
+    assert_eq!(sync.dropped(), 3);
+}
+
+#[test]
+fn sensor_off() {
+    let conf = conf();
+    let mut sync = Synchronizer::new(&conf, 120.0, Interpolation::Linear, 384).unwrap();
+    let slots = stream(9);
+
+    assert_eq!(run(&mut sync, &slots[..6]).len(), 2);
+
+    sync.bdr_change(BdrGen2::Hz240.into(), Bdr::Off);
+    let only_xl: Vec<_> = slots[6..].iter().filter(|s| s.sensor_tag == SensorType::Accelerometer).copied().collect();
+    assert!(run(&mut sync, &only_xl).is_empty());
+
+    // the timeline restarts when both sensors have a sample again
+    sync.bdr_change(BdrGen2::Hz240.into(), BdrGen2::Hz120.into());
+    let slots = stream(20);
+    let out = run(&mut sync, &slots[20..]);
+    assert_eq!(out.iter().map(|s| (s.t - T0) / 192).collect::<Vec<_>>(), [14, 16, 18]);
+}
+
+#[test]
+fn odr_change_in_stream() {
+    let conf = Config {
+        bdr_xl: BdrGen2::Hz120.into(),
+        bdr_gy: BdrGen2::Hz120.into(),
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    };
+    let samples = |range: std::ops::Range<u32>, sensors: &[SensorType]| -> Vec<OutSlot> {
+        range.flat_map(|i| sensors.iter().map(move |&s| slot(s, T0 + i * 384, i as i16))).collect()
+    };
+    let both = [SensorType::Accelerometer, SensorType::Gyroscope];
+
+    // the gyroscope is off from the 8th to the 16th time slot
+    let mut encoder = FifoEncoder::new(&conf, false, TsDecimation::X8).unwrap();
+    let mut raw = vec![RawSlot::default(); 128];
+    let mut n = encoder.encode(&samples(0..8, &both), &mut raw).unwrap() as usize;
+    n += encoder.change_bdr(T0 + 8 * 384, conf.bdr_xl, Bdr::Off, Bdr::Off, &mut raw[n..]).unwrap() as usize;
+    n += encoder.encode(&samples(8..16, &[SensorType::Accelerometer]), &mut raw[n..]).unwrap() as usize;
+    n += encoder.change_bdr(T0 + 16 * 384, conf.bdr_xl, conf.bdr_gy, Bdr::Off, &mut raw[n..]).unwrap() as usize;
+    n += encoder.encode(&samples(16..24, &both), &mut raw[n..]).unwrap() as usize;
+
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut out = vec![OutSlot::default(); n * MAX_SLOTS_PER_WORD];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode_with_overrun(&mut out, &raw, &mut out_size, n as u16, false), Status::Ok);
+    fifo.sort(&mut out, out_size);
+    out.truncate(out_size as usize);
+    assert_eq!(out.iter().filter(|s| s.sensor_tag == SensorType::BdrChange).count(), 2);
+
+    // the BdrChange slots are applied without calling bdr_change
+    let mut sync = Synchronizer::new(&conf, 120.0, Interpolation::Linear, 384).unwrap();
+    let out = run(&mut sync, &out);
+
+    let slots: Vec<_> = out.iter().map(|s| (s.t - T0) / 384).collect();
+    assert_eq!(slots, (0..8).chain(16..24).collect::<Vec<_>>());
+    assert_eq!(sync.dropped(), 0);
+}
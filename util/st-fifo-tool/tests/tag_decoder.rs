@@ -0,0 +1,112 @@
+use st_fifo_tool::*;
+
+const TAG_XL: u8 = 0x02;
+const TAG_ISPU: u8 = 0x1F; // not used by the LSM6DSV-class devices
+
+/// Application sample of the custom tag.
+#[derive(PartialEq, Debug)]
+struct IspuSample {
+    energy: u32,
+    class: u8,
+}
+
+/// Accumulates the energy over the valid outputs.
+#[derive(Default)]
+struct IspuDecoder {
+    energy: u32,
+    invalid: u32,
+}
+
+impl TagDecoder for IspuDecoder {
+    type Sample = IspuSample;
+
+    fn decode(&mut self, payload: &[u8; 6], ctx: &TagContext) -> Option<IspuSample> {
+        assert_eq!((ctx.tag, ctx.sensor_type), (TAG_ISPU, SensorType::Custom0));
+
+        // the last byte flags an invalid output
+        if payload[5] != 0 {
+            self.invalid += 1;
+            return None;
+        }
+
+        self.energy += u16::from_le_bytes([payload[0], payload[1]]) as u32;
+        Some(IspuSample { energy: self.energy, class: payload[2] })
+    }
+}
+
+fn word(tag: u8, counter: u8, payload: [u8; 6]) -> RawSlot {
+    let mut fifo_data_out = [0; 7];
+    fifo_data_out[0] = tag << 3 | counter << 1;
+    fifo_data_out[1..].copy_from_slice(&payload);
+    RawSlot { fifo_data_out }
+}
+
+#[test]
+fn custom_tag() {
+    let mut conf = Config { bdr_xl: BdrGen2::Hz120.into(), ..Config::new(DeviceType::Lsm6dsv16x) };
+    assert_eq!(conf.custom_tags.register(TAG_ISPU), Ok(SensorType::Custom0));
+
+    let raw = [
+        word(TAG_XL, 0, [0; 6]),
+        word(TAG_ISPU, 0, [0x34, 0x12, 3, 0, 0, 0]),
+        word(TAG_XL, 1, [0; 6]),
+        word(TAG_ISPU, 1, [0, 0, 0, 0, 0, 1]),
+        word(TAG_ISPU, 2, [0x01, 0x00, 7, 0, 0, 0]),
+    ];
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut decoder = IspuDecoder::default();
+    let mut custom = Vec::new();
+    let mut out = [OutSlot::default(); 8];
+    let mut out_size = 0;
+
+    let status = fifo.decode_custom(&mut out, &raw, &mut out_size, raw.len() as u16, &mut decoder, |ctx, sample| {
+        custom.push((ctx.timestamp, sample))
+    });
+    assert_eq!(status, Status::Ok);
+
+    // timed with the tag counter, the invalid output is dropped
+    assert_eq!(custom, [(0, IspuSample { energy: 0x1234, class: 3 }), (768, IspuSample { energy: 0x1235, class: 7 })]);
+    assert_eq!(decoder.invalid, 1);
+    assert_eq!(out_size, 2);
+    assert!(out[..2].iter().all(|s| s.sensor_tag == SensorType::Accelerometer));
+    assert_eq!(fifo.stats().samples(SensorType::Custom0), 2);
+    assert_eq!(fifo.stats().words, 5);
+
+    // without a decoder the words are skipped
+    let mut fifo = FifoData::init(&conf).unwrap();
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, raw.len() as u16), Status::Ok);
+    assert_eq!(out_size, 2);
+    assert_eq!(out[1].timestamp, 384);
+}
+
+#[test]
+fn registry() {
+    let mut registry = TagRegistry::new();
+
+    for (n, tag) in [0x1A, 0x1B, 0x1C, 0x1D].into_iter().enumerate() {
+        assert_eq!(registry.register(tag), SensorType::try_from(SensorType::Custom0 as u8 + n as u8).map_err(|_| Status::Err));
+    }
+
+    // full
+    assert_eq!(registry.register(0x1F), Err(Status::Err));
+    assert_eq!(registry.tags().collect::<Vec<_>>(), [0x1A, 0x1B, 0x1C, 0x1D]);
+
+    // already registered
+    let mut registry = TagRegistry::new();
+    registry.register(0x1F).unwrap();
+    assert_eq!(registry.register(0x1F), Err(Status::Err));
+}
+
+#[test]
+fn known_tags_are_rejected() {
+    for (device, tag) in [(DeviceType::Lsm6dsv16x, TAG_XL), (DeviceType::Lsm6dsv16x, 0x1E), (DeviceType::Lsm6dso, 0x20)] {
+        let mut conf = Config::new(device);
+        conf.custom_tags.register(tag).unwrap();
+        assert!(FifoData::init(&conf).is_err(), "{device:?} tag {tag:#04X}");
+    }
+
+    // the MLC tags are free on the first generation
+    let mut conf = Config::new(DeviceType::Lsm6dso);
+    conf.custom_tags.register(0x1A).unwrap();
+    assert!(FifoData::init(&conf).is_ok());
+}
@@ -0,0 +1,85 @@
+use st_fifo_tool::*;
+
+const T0: u32 = 1_000_000;
+
+fn temp_slot(timestamp: u32, temp: i16) -> OutSlot {
+    OutSlot { timestamp, sensor_tag: SensorType::Temperature, sensor_data: SensorData { data: [temp, 0, 0] } }
+}
+
+#[test]
+fn timestamps_follow_temperature_rate() {
+    let conf = Config {
+        bdr_xl: BdrGen1::Hz104.into(),
+        bdr_temp: BdrGen1::Hz13.into(),
+        ..Config::new(DeviceType::Lsm6dso)
+    };
+
+    // the temperature words land in the first 104 Hz time slot after the
+    // conversion, sometimes one slot late
+    let temp_times = [0, 3456, 6144, 9600];
+    let mut samples = Vec::new();
+
+    for i in 0..26u32 {
+        let t = T0 + i * 384;
+        samples.push(OutSlot { timestamp: t, sensor_tag: SensorType::Accelerometer, ..OutSlot::default() });
+
+        if temp_times.contains(&(i * 384)) {
+            samples.push(temp_slot(t, 0));
+        }
+    }
+
+    let mut encoder = FifoEncoder::new(&conf, false, TsDecimation::Off).unwrap();
+    let mut raw = [RawSlot::default(); 64];
+    let n = encoder.encode(&samples, &mut raw).unwrap();
+
+    let mut fifo = FifoData::init(&conf).unwrap();
+    let mut out = [OutSlot::default(); 64];
+    let mut out_size = 0;
+    assert_eq!(fifo.decode(&mut out, &raw, &mut out_size, n), Status::Ok);
+
+    let temp: Vec<u32> = out[..out_size as usize]
+        .iter()
+        .filter(|s| s.sensor_tag == SensorType::Temperature)
+        .map(|s| s.timestamp - T0)
+        .collect();
+
+    // 12.5 Hz is 3072 ticks of 25 us
+    assert_eq!(temp, [0, 3072, 6144, 9216]);
+}
+
+#[test]
+fn celsius() {
+    let conf = Config::new(DeviceType::Lsm6dsv16x);
+    let slot = temp_slot(0, 512);
+
+    assert_eq!(slot.sensor_data.to_temperature().celsius(DeviceType::Lsm6dsv16x), 27.0);
+    assert_eq!(slot.to_si(&conf), Measurement::Temperature(27.0));
+    assert_eq!(temp_slot(0, -1280).to_si(&conf), Measurement::Temperature(20.0));
+}
+
+#[test]
+fn invalid_temperature_rate() {
+    let conf = Config { bdr_temp: BdrGen2::Hz15.into(), ..Config::new(DeviceType::Lsm6dso) };
+    assert!(FifoData::init(&conf).is_err());
+
+    // accelerometer rates the temperature does not support
+    for (device, bdr_temp) in [
+        (DeviceType::Lsm6dso, Bdr::from(BdrGen1::Hz416)),
+        (DeviceType::Lsm6dso, BdrGen1::Hz26.into()),
+        (DeviceType::Lsm6dsv16x, BdrGen2::Hz120.into()),
+        (DeviceType::Ism6hg256x, BdrGen2::Hz7_5.into()),
+    ] {
+        let conf = Config { bdr_temp, ..Config::new(device) };
+        assert!(FifoData::init(&conf).is_err(), "{device:?} {bdr_temp:?}");
+    }
+
+    for (device, bdr_temp) in [
+        (DeviceType::Lsm6dso, Bdr::from(BdrGen1::Hz1_625)),
+        (DeviceType::Lsm6dso, BdrGen1::Hz52.into()),
+        (DeviceType::Lsm6dsv16x, BdrGen2::Hz1_875.into()),
+        (DeviceType::Ism6hg256x, BdrGen2::Hz60.into()),
+    ] {
+        let conf = Config { bdr_temp, ..Config::new(device) };
+        assert!(FifoData::init(&conf).is_ok(), "{device:?} {bdr_temp:?}");
+    }
+}
@@ -0,0 +1,44 @@
+use st_fifo_tool::*;
+
+#[test]
+fn nominal_tick() {
+    assert_eq!(TimeBase::new(DeviceType::Lsm6dso).tick_ps(), 25_000_000);
+    assert_eq!(TimeBase::new(DeviceType::Lsm6dsv16x).tick_ps(), 21_750_000);
+    assert_eq!(TimeBase::new(DeviceType::Lsm6dsv320x).tick_ps(), 21_750_000);
+}
+
+#[test]
+fn freq_fine_trimming() {
+    // tick = nominal / (1 + k * freq_fine): a positive FREQ_FINE shortens the tick
+    for (device, trim) in [
+        (DeviceType::Lsm6dso, [(1, 24_962_556), (-1, 25_037_556), (127, 20_999_580), (-128, 30_940_594)]),
+        (DeviceType::Lsm6dsv16x, [(1, 21_721_761), (-1, 21_778_311), (127, 18_667_925), (-128, 26_091_650)]),
+        (DeviceType::Lsm6dsv320x, [(1, 21_721_761), (-1, 21_778_311), (127, 18_667_925), (-128, 26_091_650)]),
+    ] {
+        for (freq_fine, tick_ps) in trim {
+            let time_base = TimeBase::new(device).with_freq_fine(freq_fine);
+            assert_eq!(time_base.tick_ps(), tick_ps, "{device:?} {freq_fine}");
+        }
+    }
+
+    // a zero FREQ_FINE is the nominal tick
+    assert_eq!(TimeBase::new(DeviceType::Lsm6dsv16x).with_freq_fine(0).tick_ps(), 21_750_000);
+}
+
+#[test]
+fn ticks_to_ns() {
+    let time_base = TimeBase::new(DeviceType::Lsm6dso);
+    assert_eq!(time_base.ticks_to_ns(0), 0);
+    assert_eq!(time_base.ticks_to_ns(40), 1_000_000);
+    assert_eq!(time_base.ticks_to_ns(u32::MAX), 107_374_182_375_000);
+
+    let time_base = TimeBase::new(DeviceType::Lsm6dsv16x);
+    assert_eq!(time_base.ticks_to_ns(1), 21_750);
+    assert_eq!(time_base.ticks_to_ns(384), 8_352_000); // 120 Hz sample period
+    assert_eq!(time_base.ticks_to_duration(1000), core::time::Duration::from_micros(21_750));
+
+    // 21.721761 us per tick
+    let time_base = time_base.with_freq_fine(1);
+    assert_eq!(time_base.ticks_to_ns(1), 21_721);
+    assert_eq!(time_base.ticks_to_ns(1000), 21_721_761);
+}
@@ -0,0 +1,99 @@
+use st_fifo_tool::*;
+
+const T0: u32 = 1_000_000;
+const DTIME_120HZ: u32 = 384;
+
+fn conf() -> Config {
+    Config {
+        bdr_xl: BdrGen2::Hz120.into(),
+        ts_decimation: TsDecimation::X8,
+        ..Config::new(DeviceType::Lsm6dsv16x)
+    }
+}
+
+/// Encodes `n` accelerometer samples at 120 Hz, one FIFO word each.
+fn encode(n: u32, ts_decimation: TsDecimation) -> Vec<RawSlot> {
+    let samples: Vec<_> = (0..n)
+        .map(|i| OutSlot {
+            timestamp: T0 + i * DTIME_120HZ,
+            sensor_tag: SensorType::Accelerometer,
+            sensor_data: SensorData { data: [i as i16, 0, 0] },
+        })
+        .collect();
+
+    let mut encoder = FifoEncoder::new(&conf(), false, ts_decimation).unwrap();
+    let mut raw = vec![RawSlot::default(); n as usize * 2];
+    let len = encoder.encode(&samples, &mut raw).unwrap() as usize;
+    raw.truncate(len);
+    raw
+}
+
+fn decode(fifo: &mut FifoData, raw: &[RawSlot], host_ns: Option<u64>) -> Vec<u32> {
+    let mut out = vec![OutSlot::default(); raw.len() * 3];
+    let mut out_size = 0;
+
+    assert_eq!(fifo.decode_reconstructed(&mut out, raw, &mut out_size, raw.len() as u16, false, host_ns), Status::Ok);
+    out[..out_size as usize].iter().map(|s| s.timestamp).collect()
+}
+
+fn is_ts(raw_slot: &RawSlot) -> bool {
+    raw_slot.fifo_data_out[0] >> 3 == 0x04
+}
+
+#[test]
+fn back_fill() {
+    // the read starts after the TS word: the first 8 samples are timed from 0
+    let raw = encode(16, TsDecimation::X8);
+    let mut fifo = FifoData::init(&conf()).unwrap();
+
+    assert_eq!(decode(&mut fifo, &raw[1..], None), (0..16).map(|i| T0 + i * DTIME_120HZ).collect::<Vec<_>>());
+    assert_eq!(fifo.ts_error_bound(), Some(0));
+}
+
+#[test]
+fn drift_interpolation() {
+    // the sensor runs 8 ticks slower than the nominal time slot every 8 slots
+    let mut raw = encode(24, TsDecimation::X8);
+
+    for (k, raw_slot) in raw.iter_mut().filter(|raw_slot| is_ts(raw_slot)).enumerate() {
+        let ts = u32::from_le_bytes(raw_slot.fifo_data_out[1..5].try_into().unwrap()) + k as u32 * 8;
+        raw_slot.fifo_data_out[1..5].copy_from_slice(&ts.to_le_bytes());
+    }
+
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let expected: Vec<_> = (0..24).map(|i| T0 + i * DTIME_120HZ + i.min(16)).collect();
+
+    assert_eq!(decode(&mut fifo, &raw, None), expected);
+    assert_eq!(fifo.ts_error_bound(), Some(8));
+}
+
+#[test]
+fn host_anchor() {
+    // no TS word: the samples are timed from 0 and anchored to the host clock
+    let raw: Vec<_> = encode(16, TsDecimation::Off).into_iter().filter(|raw_slot| !is_ts(raw_slot)).collect();
+    let mut fifo = FifoData::init(&Config { ts_decimation: TsDecimation::Off, ..conf() }).unwrap();
+    let h1 = 1_000_000_000;
+
+    decode(&mut fifo, &raw[..8], Some(h1));
+    assert_eq!(fifo.host_time_ns(7 * DTIME_120HZ), Some(h1));
+    assert_eq!(fifo.host_error_bound_ns(), None);
+
+    // 8 slots of 384 nominal ticks of 21.75 µs are 66.816 ms, the host measured 66.840 ms
+    let times = decode(&mut fifo, &raw[8..], Some(h1 + 66_840_000));
+    assert_eq!(times[3], 11 * DTIME_120HZ);
+    assert_eq!(fifo.host_time_ns(times[3]), Some(h1 + 33_420_000));
+    assert_eq!(fifo.host_error_bound_ns(), Some(24_000));
+    assert_eq!(fifo.ts_error_bound(), None);
+}
+
+#[test]
+fn overrun_needs_room() {
+    let raw = encode(4, TsDecimation::X8);
+    let mut fifo = FifoData::init(&conf()).unwrap();
+    let mut out_size = 0;
+
+    assert_eq!(fifo.decode_reconstructed(&mut [], &raw, &mut out_size, raw.len() as u16, true, None), Status::Err);
+    assert_eq!(out_size, 0);
+    assert_eq!(fifo.stats().overruns, 0);
+    assert_eq!(fifo.stats().words, 0);
+}
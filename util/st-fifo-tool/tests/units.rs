@@ -0,0 +1,88 @@
+use st_fifo_tool::*;
+
+fn slot(sensor_tag: SensorType, x: i16) -> OutSlot {
+    OutSlot { timestamp: 0, sensor_tag, sensor_data: SensorData { data: [x, -x, 0] } }
+}
+
+fn mg(conf: &Config, sensor_tag: SensorType, x: i16) -> f32 {
+    slot(sensor_tag, x).to_si(conf).acceleration_mg().unwrap().x
+}
+
+fn dps(conf: &Config, sensor_tag: SensorType, x: i16) -> f32 {
+    match slot(sensor_tag, x).to_si(conf) {
+        Measurement::AngularRate(v) => {
+            assert_eq!(v.y, -v.x);
+            v.x
+        }
+        m => panic!("{m:?}"),
+    }
+}
+
+fn celsius(device: DeviceType, temp: i16) -> f32 {
+    match slot(SensorType::Temperature, temp).to_si(&Config::new(device)) {
+        Measurement::Temperature(t) => t,
+        m => panic!("{m:?}"),
+    }
+}
+
+fn assert_close(value: f32, expected: f32) {
+    assert!((value - expected).abs() < 1e-3 * expected.abs().max(1.0), "{value} != {expected}");
+}
+
+#[test]
+fn fifo_ver_0() {
+    // LSM6DSO: 0.061 mg/LSB at ±2 g, 8.75 mdps/LSB at ±250 dps
+    let conf = Config { fs_gy: GyFullScale::Dps250, ..Config::new(DeviceType::Lsm6dso) };
+    assert_eq!(conf.fs_xl, XlFullScale::G2);
+    assert_close(mg(&conf, SensorType::Accelerometer, 16393), 1000.0);
+    assert_close(dps(&conf, SensorType::Gyroscope, 1000), 8.75);
+
+    // LSM6DSO32: 0.976 mg/LSB at ±32 g
+    let conf = Config { fs_xl: XlFullScale::G32, ..Config::new(DeviceType::Lsm6dso32) };
+    assert_close(mg(&conf, SensorType::Accelerometer, 1000), 976.0);
+}
+
+#[test]
+fn fifo_ver_1() {
+    // LSM6DSV16X: 0.122 mg/LSB at ±4 g, 4.375 mdps/LSB at ±125 dps, 140 mdps/LSB at ±4000 dps
+    let conf = Config { fs_xl: XlFullScale::G4, ..Config::new(DeviceType::Lsm6dsv16x) };
+    assert_eq!(conf.fs_gy, GyFullScale::Dps125);
+    assert_close(mg(&conf, SensorType::Accelerometer, 8197), 1000.0);
+    assert_close(dps(&conf, SensorType::Gyroscope, 1000), 4.375);
+
+    let conf = Config { fs_gy: GyFullScale::Dps4000, ..conf };
+    assert_close(dps(&conf, SensorType::Gyroscope, -1000), -140.0);
+}
+
+#[test]
+fn fifo_ver_2_high_g() {
+    // LSM6DSV320X: 10.417 mg/LSB at ±320 g
+    let conf = Config { fs_hg: Some(HgFullScale::G320), ..Config::new(DeviceType::Lsm6dsv320x) };
+    assert_close(mg(&conf, SensorType::HighGAccel, 3072), 32001.0);
+    assert!(FifoData::init(&conf).is_ok());
+
+    // ISM6HG256X: 7.808 mg/LSB at ±256 g, no ±320 g full scale
+    let conf = Config { fs_hg: Some(HgFullScale::G256), ..Config::new(DeviceType::Ism6hg256x) };
+    assert_close(mg(&conf, SensorType::HighGAccel, 4096), 31981.6);
+    assert!(FifoData::init(&Config { fs_hg: Some(HgFullScale::G320), ..conf }).is_err());
+
+    // LSM6DSV80X: 2.441 mg/LSB at ±80 g
+    let conf = Config { fs_hg: Some(HgFullScale::G80), ..Config::new(DeviceType::Lsm6dsv80x) };
+    assert_close(mg(&conf, SensorType::HighGAccel, 1000), 2441.0);
+
+    // no high-g full scale on a part without the high-g accelerometer
+    let conf = Config::new(DeviceType::Lsm6dsv16x);
+    assert_eq!(conf.fs_hg, None);
+    assert_eq!(slot(SensorType::HighGAccel, 1000).to_si(&conf), Measurement::Unsupported);
+}
+
+#[test]
+fn temperature_offset() {
+    // 0 LSB is 25 °C, 256 LSB/°C
+    for device in [DeviceType::Lsm6dso, DeviceType::Lsm6dsv16x, DeviceType::Lsm6dsv320x] {
+        assert_eq!(celsius(device, 0), 25.0);
+        assert_eq!(celsius(device, 256), 26.0);
+        assert_eq!(celsius(device, -6400), 0.0);
+        assert_eq!(celsius(device, 128), 25.5);
+    }
+}